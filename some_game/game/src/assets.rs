@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use game_objects::sprite::SpriteId;
+
+use quicksilver::{
+    graphics::Image,
+    Graphics, Result,
+};
+
+// Renderer side lookup from the SpriteIds game objects carry to loaded images.
+pub struct AssetRegistry {
+    images: HashMap<SpriteId, Image>,
+}
+
+impl AssetRegistry {
+
+    pub async fn load(gfx: &Graphics) -> Result<AssetRegistry> {
+        let mut images = HashMap::new();
+        for sprite in SpriteId::ALL {
+            images.insert(sprite, Image::load(gfx, file_name(sprite)).await?);
+        }
+        Ok(AssetRegistry { images })
+    }
+
+    pub fn image(&self, sprite: SpriteId) -> &Image {
+        &self.images[&sprite]
+    }
+
+}
+
+fn file_name(sprite: SpriteId) -> &'static str {
+    match sprite {
+        SpriteId::ArrowUp => r"arrow_up.png",
+        SpriteId::ArrowLeft => r"arrow_left.png",
+        SpriteId::ArrowDown => r"arrow_down.png",
+        SpriteId::ArrowRight => r"arrow_right.png",
+        SpriteId::Circle => r"circle.png",
+        SpriteId::Death => r"x.png",
+        SpriteId::Wall => r"barrier.png",
+        SpriteId::Floor => r"ice.png",
+    }
+}
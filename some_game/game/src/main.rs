@@ -1,69 +1,204 @@
-use std::num::NonZeroUsize;
+mod assets;
 
+use std::fs;
+use std::io::{self, Error as IOError, ErrorKind};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use assets::AssetRegistry;
+
+use game_objects::archetype::Archetypes;
+use game_objects::cooldown::FRAMES_PER_SECOND;
 use game_objects::game_map::GameMap;
-use game_objects::game_object::{
-    GameObject, 
-    Direction,
-};
+use game_objects::game_object::GameObject;
+use game_objects::pickup::PickupKind;
+use game_objects::replay::{self, Replay};
+use game_objects::sprite::{SpriteId, DirectionalSprites};
+use game_objects::waves::{Waves, WaveError};
+use game_objects::weapon::Weapons;
+use game_objects::world::{World, InputSnapshot};
 
-use quicksilver::Timer;
 use quicksilver::input::{Event, GamepadAxis, GamepadButton};
 use quicksilver::{
-    geom::{Vector},
-    graphics::{Color, Image},
+    geom::{Vector, Rectangle},
+    graphics::{Color, VectorFont},
     input::Key,
-    run, Graphics, Input, Result, Settings, Window,
+    run, Graphics, Input, Result, Settings, Timer, Window,
 };
 
+// a slow frame catches up at most this many ticks, then drops the rest
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+const LEVEL: &str = "levels/map_1.txt";
+const ENEMIES: &str = "enemies.toml";
+const WEAPONS: &str = "weapons.toml";
+const WAVES: &str = "waves.toml";
+// pickups closer than this fly to the player
+const MAGNET_RADIUS: f32 = 96.0;
+
 fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        },
+    };
+
+    if options.headless {
+        if let Err(err) = play_headless(options.replay.as_deref().unwrap_or_default()) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     run(
         Settings {
             title: "some_game",
             ..Settings::default()
         },
-        app,
+        move |window, gfx, input| app(window, gfx, input, options),
     );
 }
 
-async fn app(window: Window, mut gfx: Graphics, mut input: Input) -> Result<()> {
+// command line:
+//   --seed N         play with a fixed seed
+//   --record FILE    save a replay of every run that ends
+//   --replay FILE    play a recorded run back, then hand over control
+//   --headless       with --replay, play it back without a window and print the result
+#[derive(Default)]
+struct Options {
+    seed: Option<u64>,
+    record: Option<String>,
+    replay: Option<String>,
+    headless: bool,
+}
+
+impl Options {
 
-    let arrow_up = Image::load(&gfx, r"arrow_up.png").await?;
-    let arrow_left = Image::load(&gfx, r"arrow_left.png").await?;
-    let arrow_down = Image::load(&gfx, r"arrow_down.png").await?;
-    let arrow_right = Image::load(&gfx, r"arrow_right.png").await?;
+    fn from_args() -> std::result::Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            match name.as_str() {
+                "--headless" => options.headless = true,
+                "--seed" | "--record" | "--replay" => {
+                    let value = inline_value.or_else(|| args.next())
+                        .ok_or_else(|| format!("{} needs a value", name))?;
+                    match name.as_str() {
+                        "--seed" => options.seed = Some(value.parse().map_err(|_| format!("invalid seed \"{}\"", value))?),
+                        "--record" => options.record = Some(value),
+                        _ => options.replay = Some(value),
+                    }
+                },
+                _ => return Err(format!("unknown option \"{}\"", arg)),
+            }
+        }
+        if options.headless && options.replay.is_none() {
+            return Err(String::from("--headless needs --replay FILE"));
+        }
+        Ok(options)
+    }
 
-    let circle_image = Image::load(&gfx, r"circle.png").await?;
-    let death_image = Image::load(&gfx, r"x.png").await?;
-    let wall_image = Image::load(&gfx, r"barrier.png").await?;
-    let floor_image = Image::load(&gfx, r"ice.png").await?;
+}
 
-    let game_map = GameMap::new(&wall_image, &floor_image);
+// Game phases, kept as a stack so Paused sits on top of the Playing it resumes.
+#[derive(Copy, Clone, PartialEq)]
+enum Scene {
+    Title,
+    Playing,
+    Paused,
+    GameOver,
+}
 
-    let mut enemy_spawn_timer = Timer::time_per_second(0.2);
+async fn app(window: Window, mut gfx: Graphics, mut input: Input, options: Options) -> Result<()> {
 
+    let assets = AssetRegistry::load(&gfx).await?;
 
-    let mut player = GameObject::new_with_weapon(
-        Vector::new(32.0, 32.0),  
-        &arrow_up,
-        &arrow_left,
-        &arrow_down,
-        &arrow_right,
-        &circle_image
-    );
+    // menus still work without a font, they just have no text
+    let mut font = match VectorFont::load(r"font.ttf").await {
+        Ok(font) => Some(font.to_renderer(&gfx, 32.0)?),
+        Err(_) => None,
+    };
 
-    let mut enemies: Vec<GameObject> = Vec::new();
-    for _ in 0..1 {
-        enemies.push(GameObject::new_random_enemy(&circle_image));
+    let replay = match options.replay.as_ref() {
+        Some(path) => Some(Replay::load(path).map_err(|err| IOError::new(ErrorKind::InvalidData, err.to_string()))?),
+        None => None,
+    };
+    let level = replay.as_ref().map_or(LEVEL, |replay| replay.level()).to_string();
+    let game_map = GameMap::load(&level)
+        .map_err(|err| IOError::new(ErrorKind::InvalidData, err.to_string()))?;
+    let archetypes = Archetypes::load(ENEMIES)
+        .map_err(|err| IOError::new(ErrorKind::InvalidData, err.to_string()))?;
+    let weapons = Weapons::load(WEAPONS)
+        .map_err(|err| IOError::new(ErrorKind::InvalidData, err.to_string()))?;
+    let waves = load_waves(&archetypes)
+        .map_err(|err| IOError::new(ErrorKind::InvalidData, err.to_string()))?;
+    let data = data_hash(&level)?;
+    if let Some(replay) = replay.as_ref() {
+        check_data(replay, data).map_err(|err| IOError::new(ErrorKind::InvalidData, err))?;
     }
 
-    let mut bullets: Vec<GameObject> = Vec::new();
+    // a replay starts playing straight away, with the seed it was recorded with
+    let mut scenes = match replay {
+        Some(_) => vec![Scene::Title, Scene::Playing],
+        None => vec![Scene::Title],
+    };
+    let mut world = new_world(&game_map, &archetypes, &weapons, &waves, replay.as_ref().map(Replay::seed).or(options.seed));
+    let mut recording = Replay::new(world.seed(), &level, data);
+    let mut playback = replay.map(|replay| replay.inputs().clone().into_iter());
 
     let mut left_stick = Vector::new(0.0, 0.0);
     let mut right_stick = Vector::new(0.0, 0.0);
+    // stick and trigger changes wait here until the next tick picks them up
+    let mut sticks_moved = false;
+    let mut trigger_pulled = false;
+    let mut melee_pressed = false;
+    let mut reload_pressed = false;
+    let mut next_weapon = false;
+    let mut previous_weapon = false;
+
+    // the simulation steps at a fixed rate, rendering runs as fast as it can
+    let mut update_timer = Timer::time_per_second(FRAMES_PER_SECOND as f32);
 
     loop {
         while let Some(event) = input.next_event().await {
+            let scene = *scenes.last().expect("scene stack is never empty");
             match event {
+                Event::KeyboardInput(key_event) if key_event.is_down() => {
+                    match (scene, key_event.key()) {
+                        (Scene::Title, Key::Return) | (Scene::GameOver, Key::Return) => {
+                            world = new_world(&game_map, &archetypes, &weapons, &waves, options.seed);
+                            recording = Replay::new(world.seed(), &level, data);
+                            playback = None;
+                            scenes = vec![Scene::Title, Scene::Playing];
+                        },
+                        (Scene::Playing, Key::E) => {
+                            next_weapon = true;
+                        },
+                        (Scene::Playing, Key::Q) => {
+                            previous_weapon = true;
+                        },
+                        (Scene::Playing, Key::Escape) | (Scene::Playing, Key::P) => {
+                            scenes.push(Scene::Paused);
+                        },
+                        (Scene::Paused, Key::Escape) | (Scene::Paused, Key::P) => {
+                            scenes.pop();
+                        },
+                        (Scene::Paused, Key::Q) => {
+                            save_recording(&options, &recording);
+                            scenes = vec![Scene::Title];
+                        },
+                        (Scene::GameOver, Key::Escape) => {
+                            scenes = vec![Scene::Title];
+                        },
+                        _ => (),
+                    }
+                },
                 Event::GamepadAxis(axis_event) => {
                     if axis_event.axis() == GamepadAxis::RightStickX {
                         right_stick.x = axis_event.value();
@@ -78,112 +213,294 @@ async fn app(window: Window, mut gfx: Graphics, mut input: Input) -> Result<()>
                         left_stick.y = -axis_event.value();
                     }
                     // println!("left_stick x: {} y: {}", left_stick.x, left_stick.y);
-                    player.set_acceleration(left_stick);
-                    player.set_direction(right_stick);
+                    sticks_moved = true;
                 },
-                Event::GamepadButton(button_event) =>{
-                    if button_event.button() == GamepadButton::RightTrigger {
-                        player.shoot(&mut bullets);
+                Event::GamepadButton(button_event) if button_event.is_down() => {
+                    match (scene, button_event.button()) {
+                        (Scene::Playing, GamepadButton::RightTrigger) => {
+                            trigger_pulled = true;
+                        },
+                        (Scene::Playing, GamepadButton::LeftTrigger) => {
+                            melee_pressed = true;
+                        },
+                        (Scene::Playing, GamepadButton::West) => {
+                            reload_pressed = true;
+                        },
+                        (Scene::Playing, GamepadButton::RightShoulder) => {
+                            next_weapon = true;
+                        },
+                        (Scene::Playing, GamepadButton::LeftShoulder) => {
+                            previous_weapon = true;
+                        },
+                        (Scene::Title, GamepadButton::Start) | (Scene::GameOver, GamepadButton::Start) => {
+                            world = new_world(&game_map, &archetypes, &weapons, &waves, options.seed);
+                            recording = Replay::new(world.seed(), &level, data);
+                            playback = None;
+                            scenes = vec![Scene::Title, Scene::Playing];
+                        },
+                        (Scene::Playing, GamepadButton::Start) => {
+                            scenes.push(Scene::Paused);
+                        },
+                        (Scene::Paused, GamepadButton::Start) => {
+                            scenes.pop();
+                        },
+                        (Scene::Paused, GamepadButton::Select) => {
+                            save_recording(&options, &recording);
+                            scenes = vec![Scene::Title];
+                        },
+                        (Scene::GameOver, GamepadButton::Select) => {
+                            scenes = vec![Scene::Title];
+                        },
+                        _ => (),
                     }
                 },
                 _ => (),
             }
         }
-  
-        //move
-        if input.key_down(Key::A) {
-            player.move_left();
-        }
-        if input.key_down(Key::D) {
-            player.move_right();
-        }
-        if input.key_down(Key::W) {
-            player.move_up();
+
+        // only a Playing scene on top of the stack advances the simulation,
+        // so pausing also freezes spawn and shoot cooldowns
+        if scenes.last() == Some(&Scene::Playing) {
+            let mut ticks = 0;
+            while update_timer.tick() {
+                let live = InputSnapshot {
+                    move_up: input.key_down(Key::W),
+                    move_down: input.key_down(Key::S),
+                    move_left: input.key_down(Key::A),
+                    move_right: input.key_down(Key::D),
+                    aim_up: input.key_down(Key::Up),
+                    aim_down: input.key_down(Key::Down),
+                    aim_left: input.key_down(Key::Left),
+                    aim_right: input.key_down(Key::Right),
+                    shoot: input.key_down(Key::Space) || trigger_pulled,
+                    melee: input.key_down(Key::F) || melee_pressed,
+                    reload: input.key_down(Key::R) || reload_pressed,
+                    next_weapon,
+                    previous_weapon,
+                    sticks: if sticks_moved { Some((left_stick, right_stick)) } else { None },
+                };
+                sticks_moved = false;
+                trigger_pulled = false;
+                melee_pressed = false;
+                reload_pressed = false;
+                next_weapon = false;
+                previous_weapon = false;
+
+                // while a replay plays the live input is ignored
+                let replayed = playback.as_mut().and_then(Iterator::next);
+                if playback.is_some() && replayed.is_none() {
+                    println!("replay finished");
+                    playback = None;
+                }
+                let snapshot = replayed.unwrap_or(live);
+
+                recording.record(&snapshot);
+                world.tick(&snapshot);
+                if world.is_over() {
+                    save_recording(&options, &recording);
+                    scenes.pop();
+                    scenes.push(Scene::GameOver);
+                    break;
+                }
+
+                ticks += 1;
+                if ticks >= MAX_TICKS_PER_FRAME {
+                    update_timer.reset();
+                    break;
+                }
+            }
         }
-        if input.key_down(Key::S) {
-            player.move_down();
+        else {
+            // don't bank the time spent in menus
+            update_timer.reset();
         }
 
-        // direction changes
-        if input.key_down(Key::Left) {
-            player.update_direction(Direction::Left);
-        }
-        if input.key_down(Key::Right) {
-            player.update_direction(Direction::Right);
+        // how far between the last tick and the next one this frame is drawn
+        let alpha = if scenes.last() == Some(&Scene::Playing) {
+            (update_timer.elapsed().as_secs_f32() / update_timer.period().as_secs_f32()).clamp(0.0, 1.0)
         }
-        if input.key_down(Key::Up) {
-            player.update_direction(Direction::Up);
+        else {
+            1.0
+        };
+
+        let scene = *scenes.last().expect("scene stack is never empty");
+
+        gfx.clear(Color::WHITE);
+        draw_map(&mut gfx, world.map(), &assets);
+        if scene != Scene::Title {
+            draw_world(&mut gfx, &world, &assets, alpha);
+            if let Some(font) = font.as_mut() {
+                font.draw(&mut gfx, &weapon_status(&world), Color::BLACK, Vector::new(8.0, 32.0))?;
+                font.draw(&mut gfx, &format!("score {}", world.score()), Color::BLACK, Vector::new(8.0, 56.0))?;
+                font.draw(&mut gfx, &wave_status(&world), Color::BLACK, Vector::new(8.0, 80.0))?;
+            }
         }
-        if input.key_down(Key::Down) {
-            player.update_direction(Direction::Down);
+
+        let overlay_text = match scene {
+            Scene::Title => Some("some_game\n\nENTER / START to play"),
+            Scene::Paused => Some("paused\n\nESC / START to resume\nQ / SELECT to quit"),
+            Scene::GameOver => Some("game over\n\nENTER / START to restart\nESC / SELECT for title"),
+            Scene::Playing => None,
+        };
+        if let Some(text) = overlay_text {
+            gfx.fill_rect(&Rectangle::new(Vector::new(0.0, 0.0), window.size()), Color::BLACK.with_alpha(0.5));
+            if let Some(font) = font.as_mut() {
+                font.draw(&mut gfx, text, Color::WHITE, Vector::new(64.0, 128.0))?;
+            }
         }
-        if input.key_down(Key::Space) {
-            player.shoot(&mut bullets);
+
+        gfx.present(&window)?;
+    }
+}
+
+fn save_recording(options: &Options, recording: &Replay) {
+    if let Some(path) = options.record.as_ref() {
+        match recording.save(path) {
+            Ok(()) => println!("replay saved to {}", path),
+            Err(err) => eprintln!("could not save replay to {}: {}", path, err),
         }
+    }
+}
 
-        // cull bullets
-        bullets.retain(|bullet| {
-            let delete = {
-                bullet.out_of_range()
-            };
-            !delete
-        });
+fn play_headless(path: &str) -> std::result::Result<(), String> {
+    let replay = Replay::load(path).map_err(|err| err.to_string())?;
+    let game_map = GameMap::load(replay.level()).map_err(|err| err.to_string())?;
+    let archetypes = Archetypes::load(ENEMIES).map_err(|err| err.to_string())?;
+    let weapons = Weapons::load(WEAPONS).map_err(|err| err.to_string())?;
+    let waves = load_waves(&archetypes).map_err(|err| err.to_string())?;
+    check_data(&replay, data_hash(replay.level()).map_err(|err| err.to_string())?)?;
+    let mut world = new_world(&game_map, &archetypes, &weapons, &waves, Some(replay.seed()));
 
-        // let acceleration = player.acceleration();
-        // println!("player.acceleration x: {} y: {}", acceleration.x, acceleration.y);
+    let events = replay.play(&mut world);
 
-        player.accelerate();
-        player.carry_momentum(game_map.map());
-        for bullet in bullets.iter_mut(){
-            bullet.carry_momentum(game_map.map());
-        }
+    let player = world.player();
+    println!("ticks: {}", replay.inputs().len());
+    println!("events: {}", events.len());
+    println!("player: health {} at ({}, {})", player.health(), player.position().x, player.position().y);
+    println!("enemies: {}", world.enemies().len());
+    println!("over: {}", world.is_over());
+    Ok(())
+}
 
-        gfx.clear(Color::WHITE);
-        // Draw Map
-        for tile in game_map.map() {
-            gfx.draw_image(tile.image(), tile.sprite())
-        }
+// the level and every data file a world is built from, so a replay can tell
+// whether it is being played back with what it was recorded with
+fn data_hash(level: &str) -> io::Result<u64> {
+    let texts = [level, ENEMIES, WEAPONS, WAVES].iter()
+        .map(fs::read_to_string)
+        .collect::<io::Result<Vec<String>>>()?;
+    Ok(replay::data_hash(&texts.iter().map(String::as_str).collect::<Vec<_>>()))
+}
 
-        // Draw player
-        gfx.draw_image(&player.image(), player.sprite());
+fn check_data(replay: &Replay, data: u64) -> std::result::Result<(), String> {
+    if replay.data() != data {
+        return Err(String::from("the replay was recorded with a different level, enemies, weapons or waves file"));
+    }
+    Ok(())
+}
 
-        // Draw weapon
-        // gfx.draw_image(&player.weapon().image(), player.weapon().sprite());
-        // Draw Aim Line
-        gfx.stroke_path(&[player.center(), player.weapon().center()], Color::RED);
+// only waves that spawn enemies there are archetypes for
+fn load_waves(archetypes: &Archetypes) -> std::result::Result<Waves, WaveError> {
+    let waves = Waves::load(WAVES)?;
+    waves.check(archetypes)?;
+    Ok(waves)
+}
 
-        // Draw bullets
-        for bullet in bullets.iter_mut(){
-            gfx.draw_image(&bullet.image(), bullet.sprite());
-        }
+fn new_world(game_map: &GameMap, archetypes: &Archetypes, weapons: &Weapons, waves: &Waves, fixed_seed: Option<u64>) -> World {
 
-        // cull dead enemies
-        enemies.retain(|enemy| {
-            let delete = {enemy.got_shot(&bullets)};
-            !delete
-        });
+    let seed = fixed_seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos() as u64).unwrap_or(0)
+    });
+    println!("seed: {}", seed);
 
-        // spawn new enemies
-        if enemy_spawn_timer.exhaust() >= NonZeroUsize::new(1) {
-            enemies.push(GameObject::new_random_enemy(&circle_image));
-            enemy_spawn_timer.reset();
-        }
-        
-        // Draw enemies
-        for enemy in enemies.iter_mut() {
-            for bullet in bullets.iter() {
-                if bullet.collides_with(enemy){
-                    enemy.set_image(death_image.clone());
-                }
-            }
-            if enemy.collides_with(&player) {
-                player.set_image(death_image.clone());
-            }
-            enemy.patrol_for(&player);
-            enemy.carry_momentum(game_map.map());
-            gfx.draw_image(&enemy.image(), enemy.sprite());
-        }
+    let player = GameObject::new_with_weapon(
+        Vector::new(32.0, 32.0),
+        DirectionalSprites {
+            up: SpriteId::ArrowUp,
+            left: SpriteId::ArrowLeft,
+            down: SpriteId::ArrowDown,
+            right: SpriteId::ArrowRight,
+        },
+        SpriteId::Circle
+    );
 
-        gfx.present(&window)?;
+    let mut world = World::new(game_map.clone(), player, archetypes.clone(), seed);
+    world.set_weapons(weapons.clone());
+    world.set_magnet_radius(MAGNET_RADIUS);
+    world.set_waves(waves.clone());
+    world
+}
+
+// the HUD line for the player's weapon, e.g. "shotgun 3/6 (12)"
+fn weapon_status(world: &World) -> String {
+    let ammo = world.ammo();
+    if ammo.magazine() == 0 {
+        return world.weapon().to_string();
+    }
+    let spare = ammo.spare().map_or(String::new(), |spare| format!(" ({})", spare));
+    if ammo.is_reloading() {
+        return format!("{} reloading{}", world.weapon(), spare);
     }
-}
\ No newline at end of file
+    if ammo.is_out() {
+        return format!("{} out of ammo", world.weapon());
+    }
+    format!("{} {}/{}{}", world.weapon(), ammo.loaded(), ammo.magazine(), spare)
+}
+
+// the HUD line for the waves, e.g. "wave 3" or "wave 4 in 2"
+fn wave_status(world: &World) -> String {
+    let director = world.director();
+    if director.is_on_break() {
+        let seconds = director.break_frames_left().div_ceil(FRAMES_PER_SECOND);
+        return format!("wave {} in {}", director.wave() + 1, seconds);
+    }
+    format!("wave {}", director.wave())
+}
+
+fn draw_map(gfx: &mut Graphics, game_map: &GameMap, assets: &AssetRegistry) {
+    for tile in game_map.map() {
+        gfx.draw_image(assets.image(tile.image()), tile.sprite())
+    }
+}
+
+fn draw_world(gfx: &mut Graphics, world: &World, assets: &AssetRegistry, alpha: f32) {
+
+    // Draw pickups
+    for pickup in world.pickups().iter() {
+        let color = match pickup.item().kind() {
+            PickupKind::Health => Color::RED,
+            PickupKind::Ammo => Color::ORANGE,
+            PickupKind::Weapon => Color::BLUE,
+            PickupKind::Gem => Color::GREEN,
+        };
+        gfx.fill_rect(&pickup.object().interpolated_sprite(alpha), color);
+    }
+
+    let player = world.player();
+
+    // Draw player, blinking while invulnerable
+    if player.is_dead() {
+        gfx.draw_image(assets.image(SpriteId::Death), player.interpolated_sprite(alpha));
+    }
+    else if player.invulnerable_frames() % 8 < 4 {
+        gfx.draw_image(assets.image(player.image()), player.interpolated_sprite(alpha));
+    }
+
+    // Draw weapon, only while it swings
+    if player.is_swinging() {
+        gfx.draw_image(assets.image(player.weapon().image()), player.weapon().interpolated_sprite(alpha));
+    }
+    // Draw Aim Line
+    if !player.is_dead() {
+        gfx.stroke_path(&[player.interpolated_center(alpha), player.weapon().interpolated_center(alpha)], Color::RED);
+    }
+
+    // Draw bullets
+    for bullet in world.bullets().iter().chain(world.enemy_bullets().iter()) {
+        gfx.draw_image(assets.image(bullet.image()), bullet.interpolated_sprite(alpha));
+    }
+
+    // Draw enemies
+    for enemy in world.enemies().iter() {
+        gfx.draw_image(assets.image(enemy.image()), enemy.interpolated_sprite(alpha));
+    }
+}
@@ -0,0 +1,96 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use quicksilver::geom::Vector;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use game_objects::archetype::Archetypes;
+use game_objects::dungeon::{self, DungeonParams, DungeonStyle};
+use game_objects::flow_field::FlowField;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::map_layout::MapLayout;
+use game_objects::pathfinding;
+use game_objects::sprite::{SpriteId, DirectionalSprites};
+use game_objects::world::{World, InputSnapshot};
+
+fn floor_cells(layout: &MapLayout, count: usize) -> Vec<(usize, usize)> {
+    let floor: Vec<(usize, usize)> = (0..layout.height())
+        .flat_map(|y| (0..layout.width()).map(move |x| (x, y)))
+        .filter(|cell| layout.is_walkable(cell.0, cell.1))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(8);
+    (0..count).map(|_| *floor.choose(&mut rng).unwrap()).collect()
+}
+
+// every enemy finding its way to the player: one A* each, or one shared flow field
+fn navigation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("horde_navigation");
+    group.sample_size(10);
+    let params = DungeonParams { style: DungeonStyle::Caves, ..DungeonParams::default() };
+    let layout = dungeon::generate(2, 96, 96, &params);
+    let player = layout.player_spawn().unwrap();
+
+    for count in [1000, 2000, 5000] {
+        let enemies = floor_cells(&layout, count);
+
+        group.bench_with_input(BenchmarkId::new("a_star", count), &enemies, |b, enemies| {
+            b.iter(|| {
+                enemies.iter()
+                    .filter_map(|enemy| pathfinding::find_path(&layout, *enemy, black_box(player)))
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("flow_field", count), &enemies, |b, enemies| {
+            b.iter(|| {
+                let mut flow_field = FlowField::new();
+                flow_field.update(&layout, black_box(player));
+                enemies.iter()
+                    .filter_map(|enemy| flow_field.next_cell(&layout, *enemy))
+                    .count()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// a whole simulation tick with a horde chasing the player
+fn world_tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("horde_world_tick");
+    group.sample_size(10);
+    let params = DungeonParams { style: DungeonStyle::Caves, ..DungeonParams::default() };
+    let map = GameMap::generate(2, 96, 96, &params);
+
+    for count in [1000, 2000] {
+        let enemies = floor_cells(map.layout(), count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &enemies, |b, enemies| {
+            b.iter_batched(
+                || {
+                    let player = GameObject::new_with_weapon(
+                        Vector::new(0.0, 0.0),
+                        DirectionalSprites::all(SpriteId::ArrowRight),
+                        SpriteId::Circle,
+                    );
+                    let mut archetypes = Archetypes::default();
+                    let mut chaser = archetypes.get("chaser").unwrap().clone();
+                    // the whole map is in range
+                    chaser.range = 10_000.0;
+                    archetypes.insert("chaser", chaser);
+                    let mut world = World::new(map.clone(), player, archetypes, 0);
+                    for enemy in enemies.iter() {
+                        world.spawn_enemy("chaser", MapLayout::cell_center(*enemy));
+                    }
+                    world
+                },
+                |mut world| world.tick(&InputSnapshot::default()),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, navigation, world_tick);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use quicksilver::geom::{Vector, Rectangle, Shape};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use game_objects::dungeon::{self, DungeonParams, DungeonStyle};
+use game_objects::map_layout::{MapLayout, TILE_SIZE};
+
+// what check_collisions used to do: test every tile on the map
+fn scan_all_tiles(tiles: &[(Rectangle, bool)], area: &Rectangle) -> bool {
+    tiles.iter().any(|(tile, collidable)| *collidable && area.overlaps_rectangle(tile))
+}
+
+fn movers(layout: &MapLayout, count: usize) -> Vec<Rectangle> {
+    let mut rng = StdRng::seed_from_u64(4);
+    let world_width = layout.width() as f32 * TILE_SIZE;
+    let world_height = layout.height() as f32 * TILE_SIZE;
+    (0..count)
+        .map(|_| Rectangle::new(
+            Vector::new(rng.gen_range(0.0..world_width), rng.gen_range(0.0..world_height)),
+            Vector::new(12.0, 12.0),
+        ))
+        .collect()
+}
+
+fn wall_collision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wall_collision");
+    let params = DungeonParams { style: DungeonStyle::Caves, ..DungeonParams::default() };
+
+    for size in [32, 128, 256] {
+        let layout = dungeon::generate(1, size, size, &params);
+        let tiles: Vec<(Rectangle, bool)> = (0..layout.height())
+            .flat_map(|y| (0..layout.width()).map(move |x| (x, y)))
+            .map(|cell| (
+                Rectangle::new(MapLayout::cell_position(cell), Vector::new(TILE_SIZE, TILE_SIZE)),
+                layout.tile(cell.0, cell.1).unwrap().collidable,
+            ))
+            .collect();
+        // a few hundred enemies and bullets
+        let areas = movers(&layout, 300);
+        for area in areas.iter() {
+            assert_eq!(scan_all_tiles(&tiles, area), layout.collides(area));
+        }
+
+        group.bench_with_input(BenchmarkId::new("scan", size), &areas, |b, areas| {
+            b.iter(|| areas.iter().filter(|area| scan_all_tiles(&tiles, black_box(area))).count())
+        });
+        group.bench_with_input(BenchmarkId::new("grid", size), &areas, |b, areas| {
+            b.iter(|| areas.iter().filter(|area| layout.collides(black_box(area))).count())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, wall_collision);
+criterion_main!(benches);
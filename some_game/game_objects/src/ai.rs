@@ -0,0 +1,171 @@
+use quicksilver::geom::Vector;
+use serde::{Serialize, Deserialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum AiState {
+    // standing still
+    Idle,
+    // walking to a random nearby waypoint
+    Wander,
+    // going for the target it can see
+    Chase,
+    // walking to where the target was last seen
+    Search,
+    // running from the target it can see
+    Flee,
+}
+
+// Tunes when an enemy moves between states, per archetype. All times are in ticks.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AiConfig {
+    // how long to stand still before wandering off
+    pub idle_frames: u32,
+    // how far away wander waypoints are picked, in pixels
+    pub wander_radius: f32,
+    // give up on a wander waypoint after this long
+    pub wander_frames: u32,
+    // give up searching after this long
+    pub search_frames: u32,
+    // flee when health drops to this fraction of max health, 0 never flees
+    pub flee_health: f32,
+    // how far away is far enough to stop fleeing, in pixels
+    pub flee_distance: f32,
+}
+
+impl Default for AiConfig {
+    fn default() -> AiConfig {
+        AiConfig {
+            idle_frames: 60,
+            wander_radius: 160.0,
+            wander_frames: 300,
+            search_frames: 600,
+            flee_health: 0.0,
+            flee_distance: 250.0,
+        }
+    }
+}
+
+// what an enemy knows about its target this tick
+#[derive(Copy, Clone, Debug)]
+pub struct Senses {
+    pub sees_target: bool,
+    pub target_position: Vector,
+    pub distance: f32,
+    // health / max health
+    pub health: f32,
+}
+
+// Per enemy state machine. think() only decides the state, the owner acts it out
+// and keeps the wander / search route here between ticks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ai {
+    state: AiState,
+    config: AiConfig,
+    frames_in_state: u32,
+    last_known_position: Option<Vector>,
+    // None until a route is planned for the current state, empty once it is walked
+    route: Option<Vec<Vector>>,
+}
+
+impl Ai {
+
+    pub fn new(config: AiConfig) -> Ai {
+        Ai {
+            state: AiState::Idle,
+            config,
+            frames_in_state: 0,
+            last_known_position: None,
+            route: None,
+        }
+    }
+
+    pub fn think(&mut self, senses: &Senses) -> AiState {
+        self.frames_in_state = self.frames_in_state.saturating_add(1);
+        let config = &self.config;
+        let hurt = config.flee_health > 0.0 && senses.health <= config.flee_health;
+        let arrived = self.route.as_ref().is_some_and(Vec::is_empty);
+
+        let next = if senses.sees_target {
+            self.last_known_position = Some(senses.target_position);
+            if !hurt {
+                AiState::Chase
+            }
+            else if senses.distance < config.flee_distance {
+                AiState::Flee
+            }
+            else {
+                AiState::Idle
+            }
+        }
+        else {
+            match self.state {
+                AiState::Chase if self.last_known_position.is_some() => AiState::Search,
+                AiState::Chase | AiState::Flee => AiState::Idle,
+                AiState::Search if arrived || self.frames_in_state >= config.search_frames => AiState::Idle,
+                AiState::Idle if self.frames_in_state >= config.idle_frames => AiState::Wander,
+                AiState::Wander if arrived || self.frames_in_state >= config.wander_frames => AiState::Idle,
+                state => state,
+            }
+        };
+
+        if next != self.state {
+            // a search that ends without finding anything forgets the target
+            if self.state == AiState::Search && !senses.sees_target {
+                self.last_known_position = None;
+            }
+            self.state = next;
+            self.frames_in_state = 0;
+            self.route = None;
+        }
+        self.state
+    }
+
+    pub fn state(&self) -> AiState {
+        self.state
+    }
+
+    pub fn config(&self) -> &AiConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: AiConfig) {
+        self.config = config;
+    }
+
+    pub fn frames_in_state(&self) -> u32 {
+        self.frames_in_state
+    }
+
+    pub fn last_known_position(&self) -> Option<Vector> {
+        self.last_known_position
+    }
+
+    pub fn has_route(&self) -> bool {
+        self.route.is_some()
+    }
+
+    pub fn route(&self) -> Option<&Vec<Vector>> {
+        self.route.as_ref()
+    }
+
+    pub fn set_route(&mut self, route: Vec<Vector>) {
+        self.route = Some(route);
+    }
+
+    // drops waypoints within reach of position and returns the next one, if any
+    pub fn next_waypoint(&mut self, position: Vector, reach: f32) -> Option<Vector> {
+        let route = self.route.as_mut()?;
+        while route.first().is_some_and(|waypoint| (*waypoint - position).len() < reach) {
+            route.remove(0);
+        }
+        route.first().copied()
+    }
+
+}
+
+impl Default for Ai {
+    fn default() -> Ai {
+        Ai::new(AiConfig::default())
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use quicksilver::geom::Vector;
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use crate::ai::AiConfig;
+use crate::game_object::{GameObject, Behavior};
+use crate::pickup::Loot;
+use crate::sprite::SpriteId;
+use crate::weapon::Weapon;
+
+// Enemy types, read from a TOML file with one table per archetype:
+//
+//   [chaser]
+//   size = [12.0, 12.0]
+//   speed = 0.1          # acceleration per tick while moving
+//   max_speed = 4.0
+//   range = 300.0        # how far away it notices the player
+//   health = 3
+//   damage = 1           # contact damage
+//   sprite = "Circle"
+//   behavior = "chase"   # or "shoot"
+//   shots_per_second = 1.0  # needed by shooters, above 0
+//   ricochets = 0        # walls a shot bounces off before it is spent
+//   spawn_weight = 1     # how often random spawns pick it, 0 for never
+//
+//   [chaser.ai]          # optional, see AiConfig for the fields and defaults
+//   flee_health = 0.3
+//
+//   [[chaser.loot]]      # optional, any number of drops, each rolled on death
+//   chance = 0.25
+//   item = "health"      # or "ammo" (rounds), "weapon" (name) or "gem" (score)
+//   amount = 1
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Archetype {
+    pub size: [f32; 2],
+    pub speed: f32,
+    pub max_speed: f32,
+    pub range: f32,
+    pub health: i32,
+    #[serde(default)]
+    pub damage: i32,
+    pub sprite: SpriteId,
+    pub behavior: Behavior,
+    #[serde(default)]
+    pub shots_per_second: f32,
+    #[serde(default)]
+    pub ricochets: u32,
+    #[serde(default = "default_invulnerability")]
+    pub invulnerability: u32,
+    #[serde(default = "default_spawn_weight")]
+    pub spawn_weight: u32,
+    #[serde(default)]
+    pub ai: AiConfig,
+    #[serde(default)]
+    pub loot: Vec<Loot>,
+}
+
+fn default_invulnerability() -> u32 {
+    10
+}
+
+fn default_spawn_weight() -> u32 {
+    1
+}
+
+impl Archetype {
+
+    pub fn spawn(&self, position: Vector) -> GameObject {
+        let mut enemy = GameObject::new_enemy(position, self.sprite);
+        enemy.set_size(Vector::new(self.size[0], self.size[1]));
+        enemy.set_speed(self.speed);
+        enemy.set_max_speed(self.max_speed);
+        enemy.set_range(self.range);
+        enemy.set_max_health(self.health);
+        enemy.set_damage(self.damage);
+        enemy.set_invulnerability(self.invulnerability);
+        enemy.set_behavior(self.behavior);
+        enemy.set_ai_config(self.ai);
+        enemy.set_loot(self.loot.clone());
+        if self.behavior == Behavior::Shoot {
+            enemy.set_weapon(GameObject::new_weapon(position, SpriteId::Circle));
+            enemy.equip(&Weapon {
+                shots_per_second: self.shots_per_second,
+                range: self.range,
+                ricochets: self.ricochets,
+                ..Weapon::default()
+            });
+        }
+        enemy
+    }
+
+    // values that load fine but make no sense in the game
+    fn check(&self, name: &str) -> Result<(), ArchetypeError> {
+        let positive = |value: f32| value > 0.0 && value.is_finite();
+        let invalid = |problem: &str| Err(ArchetypeError::Parse(format!("{} {}", name, problem)));
+        // dead from the start, it would never die, drop loot or count as a kill
+        if self.health <= 0 {
+            return invalid("needs a health above 0");
+        }
+        if !self.size.iter().all(|side| positive(*side)) {
+            return invalid("needs a size above 0");
+        }
+        if !positive(self.max_speed) {
+            return invalid("needs a max_speed above 0");
+        }
+        // left out or 0, a shooter would never fire, below 0 it would fire every tick
+        if self.behavior == Behavior::Shoot && !positive(self.shots_per_second) {
+            return invalid("shoots, so it needs a shots_per_second above 0");
+        }
+        if !self.loot.iter().all(|loot| (0.0..=1.0).contains(&loot.chance)) {
+            return invalid("has a loot chance outside 0 to 1");
+        }
+        Ok(())
+    }
+
+}
+
+#[derive(Debug)]
+pub enum ArchetypeError {
+    Io(io::Error),
+    Parse(String),
+    Empty,
+}
+
+impl fmt::Display for ArchetypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchetypeError::Io(err) => write!(f, "could not read enemy archetypes: {}", err),
+            ArchetypeError::Parse(message) => write!(f, "invalid enemy archetypes: {}", message),
+            ArchetypeError::Empty => write!(f, "no enemy archetypes defined"),
+        }
+    }
+}
+
+impl std::error::Error for ArchetypeError {}
+
+impl From<io::Error> for ArchetypeError {
+    fn from(err: io::Error) -> ArchetypeError {
+        ArchetypeError::Io(err)
+    }
+}
+
+// Every archetype by name. Sorted, so picking one with the world's rng is deterministic.
+#[derive(Clone, Debug)]
+pub struct Archetypes {
+    archetypes: BTreeMap<String, Archetype>,
+}
+
+impl Archetypes {
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Archetypes, ArchetypeError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Archetype> {
+        self.archetypes.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.archetypes.keys().map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: &str, archetype: Archetype) {
+        self.archetypes.insert(name.to_string(), archetype);
+    }
+
+    // None when there is no archetype with that name
+    pub fn spawn(&self, name: &str, position: Vector) -> Option<GameObject> {
+        self.get(name).map(|archetype| archetype.spawn(position))
+    }
+
+    // picks an archetype by spawn_weight, None when every weight is 0
+    pub fn choose(&self, rng: &mut StdRng) -> Option<&str> {
+        // summed as u64, so no number of u32 weights can overflow it
+        let total: u64 = self.archetypes.values().map(|archetype| archetype.spawn_weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = rng.gen_range(0..total);
+        for (name, archetype) in self.archetypes.iter() {
+            if pick < archetype.spawn_weight as u64 {
+                return Some(name);
+            }
+            pick -= archetype.spawn_weight as u64;
+        }
+        None
+    }
+
+}
+
+// a single chaser, what every enemy used to be
+impl Default for Archetypes {
+    fn default() -> Archetypes {
+        let mut archetypes = Archetypes { archetypes: BTreeMap::new() };
+        archetypes.insert("chaser", Archetype {
+            size: [12.0, 12.0],
+            speed: 0.1,
+            max_speed: 4.0,
+            range: 300.0,
+            health: 3,
+            damage: 1,
+            sprite: SpriteId::Circle,
+            behavior: Behavior::Chase,
+            shots_per_second: 0.0,
+            ricochets: 0,
+            invulnerability: default_invulnerability(),
+            spawn_weight: default_spawn_weight(),
+            ai: AiConfig::default(),
+            loot: Vec::new(),
+        });
+        archetypes
+    }
+}
+
+impl FromStr for Archetypes {
+    type Err = ArchetypeError;
+
+    fn from_str(text: &str) -> Result<Archetypes, ArchetypeError> {
+        let archetypes: BTreeMap<String, Archetype> = toml::from_str(text)
+            .map_err(|err| ArchetypeError::Parse(err.to_string()))?;
+        if archetypes.is_empty() {
+            return Err(ArchetypeError::Empty);
+        }
+        for (name, archetype) in archetypes.iter() {
+            archetype.check(name)?;
+        }
+        Ok(Archetypes { archetypes })
+    }
+}
@@ -0,0 +1,65 @@
+use quicksilver::geom::{Vector, Rectangle, Shape};
+
+// Where a moving rectangle first touches another.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Hit {
+    // fraction of the travel covered before touching, 0 to 1
+    pub time: f32,
+    // which side was hit, pointing back at the mover: one of (±1, 0) or (0, ±1)
+    pub normal: Vector,
+    // the mover's top left at the moment of contact
+    pub position: Vector,
+}
+
+// Swept AABB: when, moving by travel, a rectangle starts to overlap target.
+// None when it never does, or already overlaps it at the start, so anything stuck
+// inside a wall can still get out. Touching edges don't overlap, same as quicksilver,
+// so a mover sliding along a wall isn't caught on it.
+pub fn sweep(moving: &Rectangle, travel: Vector, target: &Rectangle) -> Option<Hit> {
+    let (entry_x, exit_x) = axis_times(moving.pos.x, moving.size.x, travel.x, target.pos.x, target.size.x);
+    let (entry_y, exit_y) = axis_times(moving.pos.y, moving.size.y, travel.y, target.pos.y, target.size.y);
+
+    let entry = entry_x.max(entry_y);
+    let exit = exit_x.min(exit_y);
+    if entry >= exit || !(0.0..1.0).contains(&entry) {
+        return None;
+    }
+
+    let mut position = moving.pos + travel * entry;
+    // snap to the edge itself, so rounding never leaves the mover a hair inside
+    let normal = if entry_x > entry_y {
+        position.x = if travel.x > 0.0 { target.pos.x - moving.size.x } else { target.pos.x + target.size.x };
+        Vector::new(-travel.x.signum(), 0.0)
+    }
+    else {
+        position.y = if travel.y > 0.0 { target.pos.y - moving.size.y } else { target.pos.y + target.size.y };
+        Vector::new(0.0, -travel.y.signum())
+    };
+    Some(Hit { time: entry, normal, position })
+}
+
+// whether a rectangle overlaps target anywhere along its way
+pub fn passes_through(moving: &Rectangle, travel: Vector, target: &Rectangle) -> bool {
+    moving.overlaps_rectangle(target) || sweep(moving, travel, target).is_some()
+}
+
+// the box covering a rectangle from where it starts to where travel takes it
+pub fn swept_bounds(moving: &Rectangle, travel: Vector) -> Rectangle {
+    let end = moving.pos + travel;
+    let top_left = Vector::new(moving.pos.x.min(end.x), moving.pos.y.min(end.y));
+    Rectangle::new(top_left, moving.size + Vector::new(travel.x.abs(), travel.y.abs()))
+}
+
+// When, along one axis, the two spans start and stop overlapping (strictly).
+// Standing still, that is always or never.
+fn axis_times(start: f32, length: f32, travel: f32, target_start: f32, target_length: f32) -> (f32, f32) {
+    if travel == 0.0 {
+        if start < target_start + target_length && target_start < start + length {
+            return (f32::NEG_INFINITY, f32::INFINITY);
+        }
+        return (f32::INFINITY, f32::NEG_INFINITY);
+    }
+    let near = if travel > 0.0 { target_start - (start + length) } else { target_start + target_length - start };
+    let far = if travel > 0.0 { target_start + target_length - start } else { target_start - (start + length) };
+    (near / travel, far / travel)
+}
@@ -0,0 +1,45 @@
+use serde::{Serialize, Deserialize};
+
+// Frame counting replacement for quicksilver's Timer.
+// It only advances when the simulation ticks it, so pausing the game pauses it too.
+
+// the fixed rate the simulation is stepped at, whatever the display does
+pub const FRAMES_PER_SECOND: u32 = 60;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Cooldown {
+    period: u32,
+    elapsed: u32,
+}
+
+impl Cooldown {
+
+    pub fn with_frames(period: u32) -> Cooldown {
+        Cooldown { period, elapsed: 0 }
+    }
+
+    pub fn time_per_second(times: f32) -> Cooldown {
+        Cooldown::with_frames((FRAMES_PER_SECOND as f32 / times).round() as u32)
+    }
+
+    pub fn tick(&mut self) {
+        self.elapsed = self.elapsed.saturating_add(1);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.elapsed >= self.period
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0;
+    }
+
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.period.saturating_sub(self.elapsed)
+    }
+
+}
@@ -0,0 +1,309 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::map_layout::{MapLayout, Tile};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DungeonStyle {
+    // binary space partition rooms joined by corridors
+    Rooms,
+    // cellular automata caves
+    Caves,
+}
+
+#[derive(Copy, Clone)]
+pub struct DungeonParams {
+    pub style: DungeonStyle,
+    // smallest room side, in tiles
+    pub min_room_size: usize,
+    // chance a cave cell starts as wall
+    pub wall_chance: f64,
+    pub smoothing_steps: usize,
+    pub enemy_spawns: usize,
+    // enemy spawns are kept at least this many steps away from the player, when possible
+    pub min_spawn_distance: usize,
+}
+
+impl Default for DungeonParams {
+    fn default() -> DungeonParams {
+        DungeonParams {
+            style: DungeonStyle::Rooms,
+            min_room_size: 4,
+            wall_chance: 0.45,
+            smoothing_steps: 5,
+            enemy_spawns: 5,
+            min_spawn_distance: 8,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Room {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Room {
+
+    fn center(&self) -> (usize, usize) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+}
+
+// Builds a layout from a seed; the same seed and params always give the same map.
+// All floor is connected and spawns are placed on floor.
+pub fn generate(seed: u64, width: usize, height: usize, params: &DungeonParams) -> MapLayout {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut layout = MapLayout::new(width, height, Tile::wall());
+
+    // need at least one interior cell inside the outer wall
+    if width < 3 || height < 3 {
+        return layout;
+    }
+
+    match params.style {
+        DungeonStyle::Rooms => carve_rooms(&mut layout, &mut rng, params),
+        DungeonStyle::Caves => carve_caves(&mut layout, &mut rng, params),
+    }
+
+    keep_largest_region(&mut layout);
+    place_spawns(&mut layout, &mut rng, params);
+
+    layout
+}
+
+fn carve_rooms(layout: &mut MapLayout, rng: &mut StdRng, params: &DungeonParams) {
+    let min_room = params.min_room_size.max(1);
+    let area = Room { x: 1, y: 1, width: layout.width() - 2, height: layout.height() - 2 };
+    let mut rooms = Vec::new();
+    split(layout, rng, area, min_room, &mut rooms);
+}
+
+// Splits the area in two until it is too small, then carves a room in it.
+// Returns one room of this subtree so the parent can join it to its sibling.
+fn split(layout: &mut MapLayout, rng: &mut StdRng, area: Room, min_room: usize, rooms: &mut Vec<Room>) -> Room {
+    // a leaf needs space for a room plus a one tile margin on each side
+    let min_leaf = min_room + 2;
+    let can_split_x = area.width >= min_leaf * 2;
+    let can_split_y = area.height >= min_leaf * 2;
+
+    if !can_split_x && !can_split_y {
+        let room = carve_room(layout, rng, area, min_room);
+        rooms.push(room);
+        return room;
+    }
+
+    let split_x = if can_split_x && can_split_y {
+        // prefer cutting the long side
+        if area.width > area.height { true }
+        else if area.height > area.width { false }
+        else { rng.gen_bool(0.5) }
+    }
+    else {
+        can_split_x
+    };
+
+    let (first, second) = if split_x {
+        let cut = rng.gen_range(min_leaf..=area.width - min_leaf);
+        (
+            Room { width: cut, ..area },
+            Room { x: area.x + cut, width: area.width - cut, ..area },
+        )
+    }
+    else {
+        let cut = rng.gen_range(min_leaf..=area.height - min_leaf);
+        (
+            Room { height: cut, ..area },
+            Room { y: area.y + cut, height: area.height - cut, ..area },
+        )
+    };
+
+    let first_room = split(layout, rng, first, min_room, rooms);
+    let second_room = split(layout, rng, second, min_room, rooms);
+    carve_corridor(layout, rng, first_room.center(), second_room.center());
+
+    if rng.gen_bool(0.5) { first_room } else { second_room }
+}
+
+fn carve_room(layout: &mut MapLayout, rng: &mut StdRng, area: Room, min_room: usize) -> Room {
+    // leaves smaller than a full room still get whatever fits
+    let max_width = area.width.saturating_sub(2).max(1);
+    let max_height = area.height.saturating_sub(2).max(1);
+    let width = rng.gen_range(min_room.min(max_width)..=max_width);
+    let height = rng.gen_range(min_room.min(max_height)..=max_height);
+    let x = area.x + rng.gen_range(0..=area.width - width);
+    let y = area.y + rng.gen_range(0..=area.height - height);
+
+    for cell_y in y..y + height {
+        for cell_x in x..x + width {
+            layout.set_tile(cell_x, cell_y, Tile::floor());
+        }
+    }
+
+    Room { x, y, width, height }
+}
+
+fn carve_corridor(layout: &mut MapLayout, rng: &mut StdRng, from: (usize, usize), to: (usize, usize)) {
+    // L shaped, randomly bending horizontally or vertically first
+    let corner = if rng.gen_bool(0.5) { (to.0, from.1) } else { (from.0, to.1) };
+    for (start, end) in [(from, corner), (corner, to)] {
+        for x in start.0.min(end.0)..=start.0.max(end.0) {
+            for y in start.1.min(end.1)..=start.1.max(end.1) {
+                layout.set_tile(x, y, Tile::floor());
+            }
+        }
+    }
+}
+
+fn carve_caves(layout: &mut MapLayout, rng: &mut StdRng, params: &DungeonParams) {
+    let (width, height) = (layout.width(), layout.height());
+    let mut walls = vec![true; width * height];
+    // NaN has no place between 0 and 1, and gen_bool panics on it
+    let wall_chance = if params.wall_chance.is_nan() {
+        DungeonParams::default().wall_chance
+    }
+    else {
+        params.wall_chance.clamp(0.0, 1.0)
+    };
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            walls[y * width + x] = rng.gen_bool(wall_chance);
+        }
+    }
+
+    for _ in 0..params.smoothing_steps {
+        let mut next = walls.clone();
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let mut wall_neighbours = 0;
+                for neighbour_y in y - 1..=y + 1 {
+                    for neighbour_x in x - 1..=x + 1 {
+                        if (neighbour_x, neighbour_y) != (x, y) && walls[neighbour_y * width + neighbour_x] {
+                            wall_neighbours += 1;
+                        }
+                    }
+                }
+                next[y * width + x] = wall_neighbours > 4 || (walls[y * width + x] && wall_neighbours == 4);
+            }
+        }
+        walls = next;
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            if !walls[y * width + x] {
+                layout.set_tile(x, y, Tile::floor());
+            }
+        }
+    }
+}
+
+fn is_floor(layout: &MapLayout, cell: (usize, usize)) -> bool {
+    layout.tile(cell.0, cell.1).is_some_and(|tile| !tile.collidable)
+}
+
+// up, down, left and right; off the map at 0 they wrap to usize::MAX, which is never floor
+fn neighbours((x, y): (usize, usize)) -> [(usize, usize); 4] {
+    [
+        (x.wrapping_sub(1), y),
+        (x + 1, y),
+        (x, y.wrapping_sub(1)),
+        (x, y + 1),
+    ]
+}
+
+// breadth first step counts from start over floor, None where unreachable
+fn distances_from(layout: &MapLayout, start: (usize, usize)) -> Vec<Option<usize>> {
+    let width = layout.width();
+    let mut distances = vec![None; width * layout.height()];
+    let mut queue = VecDeque::from([start]);
+    distances[start.1 * width + start.0] = Some(0);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[y * width + x].unwrap_or(0);
+        for neighbour in neighbours((x, y)) {
+            if is_floor(layout, neighbour) && distances[neighbour.1 * width + neighbour.0].is_none() {
+                distances[neighbour.1 * width + neighbour.0] = Some(distance + 1);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    distances
+}
+
+// Walls off every floor region except the biggest one. One flood fill labels
+// each region, so every cell is visited once however many regions there are.
+fn keep_largest_region(layout: &mut MapLayout) {
+    let width = layout.width();
+    let mut region_of: Vec<Option<usize>> = vec![None; width * layout.height()];
+    let mut region_sizes = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for y in 0..layout.height() {
+        for x in 0..width {
+            if !is_floor(layout, (x, y)) || region_of[y * width + x].is_some() {
+                continue;
+            }
+            let region = region_sizes.len();
+            let mut size = 0;
+            region_of[y * width + x] = Some(region);
+            queue.push_back((x, y));
+            while let Some(cell) = queue.pop_front() {
+                size += 1;
+                for neighbour in neighbours(cell) {
+                    if is_floor(layout, neighbour) && region_of[neighbour.1 * width + neighbour.0].is_none() {
+                        region_of[neighbour.1 * width + neighbour.0] = Some(region);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+            region_sizes.push(size);
+        }
+    }
+
+    let largest = (0..region_sizes.len()).max_by_key(|region| region_sizes[*region]);
+    for (index, region) in region_of.iter().enumerate() {
+        if region.is_some() && *region != largest {
+            layout.set_tile(index % width, index / width, Tile::wall());
+        }
+    }
+}
+
+fn place_spawns(layout: &mut MapLayout, rng: &mut StdRng, params: &DungeonParams) {
+    let width = layout.width();
+    let floor: Vec<(usize, usize)> = (0..layout.height())
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|cell| is_floor(layout, *cell))
+        .collect();
+
+    let player = match floor.choose(rng) {
+        Some(cell) => *cell,
+        None => return,
+    };
+    layout.set_player_spawn(player);
+
+    let distances = distances_from(layout, player);
+    let distance = |cell: &(usize, usize)| distances[cell.1 * width + cell.0].unwrap_or(0);
+
+    let mut candidates: Vec<(usize, usize)> = floor.iter()
+        .copied()
+        .filter(|cell| distance(cell) >= params.min_spawn_distance)
+        .collect();
+    if candidates.is_empty() {
+        // small maps: fall back to the cells furthest from the player
+        let furthest = floor.iter().map(distance).max().unwrap_or(0);
+        candidates = floor.iter().copied().filter(|cell| *cell != player && distance(cell) == furthest).collect();
+    }
+
+    for cell in candidates.choose_multiple(rng, params.enemy_spawns) {
+        layout.add_enemy_spawn(*cell);
+    }
+}
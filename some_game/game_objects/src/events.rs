@@ -0,0 +1,26 @@
+use quicksilver::geom::Vector;
+
+use crate::GameObjectType;
+use crate::pickup::PickupKind;
+
+// Things that happened during a frame that other systems may want to react to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    Damaged { kind: GameObjectType, amount: i32, position: Vector },
+    Died { kind: GameObjectType, position: Vector },
+    // a projectile hit a wall and is gone
+    Impact { position: Vector },
+    // a projectile bounced off a wall
+    Ricochet { position: Vector },
+    // the player's last shot emptied the magazine, and whether there is
+    // anything left to reload it with
+    MagazineEmpty { out_of_ammo: bool },
+    // the player's reload finished
+    Reloaded,
+    // the player walked over a pickup and used it
+    PickedUp { kind: PickupKind, position: Vector },
+    // the break is over and the wave with this number starts spawning
+    WaveStarted { wave: u32 },
+    // everything the wave spawned is dead, a break follows
+    WaveCleared { wave: u32 },
+}
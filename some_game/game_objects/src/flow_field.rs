@@ -0,0 +1,117 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use quicksilver::geom::Vector;
+
+use crate::map_layout::MapLayout;
+use crate::pathfinding;
+
+// Dijkstra map: the walking cost from every cell to one target cell.
+// Built once per target and shared, so any number of enemies can find their
+// way to the player by stepping downhill instead of each running A*.
+pub struct FlowField {
+    width: usize,
+    height: usize,
+    target: Option<(usize, usize)>,
+    costs: Vec<u32>,
+}
+
+impl FlowField {
+
+    pub fn new() -> FlowField {
+        FlowField {
+            width: 0,
+            height: 0,
+            target: None,
+            costs: Vec::new(),
+        }
+    }
+
+    pub fn target(&self) -> Option<(usize, usize)> {
+        self.target
+    }
+
+    // Rebuilds the field towards target, unless it already points there.
+    // Returns whether anything was recomputed.
+    pub fn update(&mut self, layout: &MapLayout, target: (usize, usize)) -> bool {
+        let same_map = self.width == layout.width() && self.height == layout.height();
+        if same_map && self.target == Some(target) {
+            return false;
+        }
+
+        self.width = layout.width();
+        self.height = layout.height();
+        self.target = Some(target);
+        self.costs.clear();
+        self.costs.resize(self.width * self.height, u32::MAX);
+        if !layout.is_walkable(target.0, target.1) {
+            return true;
+        }
+
+        let width = self.width;
+        let mut open = BinaryHeap::from([(Reverse(0), target)]);
+        self.costs[target.1 * width + target.0] = 0;
+
+        while let Some((Reverse(cost), cell)) = open.pop() {
+            if cost > self.costs[cell.1 * width + cell.0] {
+                continue;
+            }
+            for (next, step_cost) in pathfinding::neighbours(layout, cell) {
+                let next_cost = cost + step_cost;
+                if next_cost < self.costs[next.1 * width + next.0] {
+                    self.costs[next.1 * width + next.0] = next_cost;
+                    open.push((Reverse(next_cost), next));
+                }
+            }
+        }
+        true
+    }
+
+    // None for walls, cells off the map and cells the target can't be reached from
+    pub fn cost(&self, cell: (usize, usize)) -> Option<u32> {
+        if cell.0 >= self.width || cell.1 >= self.height {
+            return None;
+        }
+        match self.costs[cell.1 * self.width + cell.0] {
+            u32::MAX => None,
+            cost => Some(cost),
+        }
+    }
+
+    // the cheapest neighbour, None at the target or where it can't be reached
+    pub fn next_cell(&self, layout: &MapLayout, cell: (usize, usize)) -> Option<(usize, usize)> {
+        let cost = self.cost(cell)?;
+        pathfinding::neighbours(layout, cell)
+            .filter_map(|(next, _)| Some((self.cost(next)?, next)))
+            .filter(|(next_cost, _)| *next_cost < cost)
+            .min()
+            .map(|(_, next)| next)
+    }
+
+    // Up to steps tile centers leading downhill from a world position,
+    // empty when there is nowhere to go.
+    pub fn waypoints(&self, layout: &MapLayout, from: Vector, steps: usize) -> Vec<Vector> {
+        let mut waypoints = Vec::new();
+        let mut cell = match layout.cell_at(from) {
+            Some(cell) => cell,
+            None => return waypoints,
+        };
+        while waypoints.len() < steps {
+            match self.next_cell(layout, cell) {
+                Some(next) => {
+                    waypoints.push(MapLayout::cell_center(next));
+                    cell = next;
+                },
+                None => break,
+            }
+        }
+        waypoints
+    }
+
+}
+
+impl Default for FlowField {
+    fn default() -> FlowField {
+        FlowField::new()
+    }
+}
@@ -1,80 +1,152 @@
-use quicksilver::graphics::Image;
-use quicksilver::geom::Vector;
+use std::path::Path;
+use std::str::FromStr;
+
+use quicksilver::geom::{Vector, Rectangle};
 
 use crate::GameObjectType;
+use crate::collision::Hit;
 use crate::game_object::GameObject;
+use crate::map_layout::{MapLayout, MapError, Trigger, TILE_SIZE};
+use crate::pathfinding;
+use crate::pickup::Item;
+use crate::tiled;
+use crate::dungeon::{self, DungeonParams};
+use crate::sprite::SpriteId;
 
-const MAP_1: [[GameObjectType;32];24] = [
-    [GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Floor, GameObjectType::Wall],
-    [GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall, GameObjectType::Wall],
-    
-];
-
+#[derive(Clone)]
 pub struct GameMap {
+    layout: MapLayout,
     map: Vec<GameObject>
 }
 
 impl GameMap {
 
-    pub fn new(wall_image: &Image, floor_image: &Image) -> GameMap {
+    pub fn new(layout: MapLayout) -> GameMap {
         let mut new_map:Vec<GameObject> = Vec::new();
-        for (y, el) in MAP_1.iter().enumerate() {
-            for (x, obj_type) in el.iter().enumerate(){
-                let mut x_coord = x as f32;
-                let mut y_coord = y as f32;
-                x_coord *= 32.0;
-                y_coord *= 32.0;
-                let position = Vector::new(x_coord, y_coord);
-
-                if *obj_type == GameObjectType::Floor {
-
-                    new_map.push(GameObject::new_floor(
-                        position,
-                        floor_image
-                    ));
-
-                }
-                else if *obj_type == GameObjectType::Wall {
+        for y in 0..layout.height() {
+            for x in 0..layout.width() {
+                let position = MapLayout::cell_position((x, y));
+                let tile = layout.tile(x, y).expect("cell inside layout");
 
-                    new_map.push(GameObject::new_wall(
-                        position,
-                        wall_image
-                    ));
-                    
+                let mut tile_object = if tile.kind == GameObjectType::Wall {
+                    GameObject::new_wall(position, SpriteId::Wall)
                 }
+                else {
+                    GameObject::new_floor(position, SpriteId::Floor)
+                };
+                tile_object.set_collidable(tile.collidable);
+                new_map.push(tile_object);
             }
         }
         
 
         GameMap{
+            layout,
             map: new_map
         }
     }
 
+    pub fn load(path: impl AsRef<Path>) -> Result<GameMap, MapError> {
+        let layout = MapLayout::load(path)?;
+        Ok(GameMap::new(layout))
+    }
+
+    pub fn load_tiled(path: impl AsRef<Path>) -> Result<GameMap, MapError> {
+        let layout = tiled::load(path)?;
+        Ok(GameMap::new(layout))
+    }
+
+    pub fn generate(
+        seed: u64,
+        width: usize,
+        height: usize,
+        params: &DungeonParams,
+    ) -> GameMap {
+        let layout = dungeon::generate(seed, width, height, params);
+        GameMap::new(layout)
+    }
+
     pub fn map(&self) -> &Vec<GameObject> {
         &self.map
     }
 
-}
\ No newline at end of file
+    // map tiles under a rectangle, looked up by grid cell
+    pub fn tiles_overlapping<'a>(&'a self, area: &Rectangle) -> impl Iterator<Item = &'a GameObject> + 'a {
+        let width = self.layout.width();
+        self.layout.cells_overlapping(area).map(move |(x, y)| &self.map[y * width + x])
+    }
+
+    pub fn collides(&self, area: &Rectangle) -> bool {
+        self.layout.collides(area)
+    }
+
+    // the first wall a rectangle runs into moving by travel, if any
+    pub fn sweep(&self, area: &Rectangle, travel: Vector) -> Option<Hit> {
+        self.layout.sweep(area, travel)
+    }
+
+    // Whether a body can slide in a straight line from where it is to the new
+    // top left position without touching a wall. Checked in steps of at most
+    // half the body or half a tile, so thin walls can't be stepped over.
+    pub fn is_clear(&self, area: &Rectangle, to: Vector) -> bool {
+        let travel = to - area.pos;
+        let step = area.size.x.min(area.size.y).min(TILE_SIZE) / 2.0;
+        let steps = (travel.len() / step.max(1.0)).ceil().max(1.0) as usize;
+        (1..=steps).all(|index| {
+            let position = area.pos + travel * (index as f32 / steps as f32);
+            !self.collides(&Rectangle::new(position, area.size))
+        })
+    }
+
+    // whether a straight line between two world positions misses every wall
+    pub fn has_line_of_sight(&self, from: Vector, to: Vector) -> bool {
+        self.layout.first_wall_on_line(from, to).is_none()
+    }
+
+    // where a ray from one position towards another first hits a wall, if it does
+    pub fn raycast(&self, from: Vector, to: Vector) -> Option<(usize, usize)> {
+        self.layout.first_wall_on_line(from, to)
+    }
+
+    // Tile center waypoints from one world position to another, both ends included.
+    // None when either end is in a wall or off the map, or they aren't connected.
+    pub fn find_path(&self, from: Vector, to: Vector) -> Option<Vec<Vector>> {
+        let start = self.layout.cell_at(from)?;
+        let goal = self.layout.cell_at(to)?;
+        let cells = pathfinding::find_path(&self.layout, start, goal)?;
+        Some(cells.into_iter().map(MapLayout::cell_center).collect())
+    }
+
+    pub fn layout(&self) -> &MapLayout {
+        &self.layout
+    }
+
+    pub fn player_spawn(&self) -> Option<Vector> {
+        self.layout.player_spawn().map(MapLayout::cell_position)
+    }
+
+    pub fn enemy_spawns(&self) -> Vec<Vector> {
+        self.layout.enemy_spawns().iter().copied().map(MapLayout::cell_position).collect()
+    }
+
+    // the center of each pickup's tile, and what lies there
+    pub fn pickups(&self) -> Vec<(Vector, Item)> {
+        self.layout.pickups().iter()
+            .map(|(cell, item)| (MapLayout::cell_center(*cell), item.clone()))
+            .collect()
+    }
+
+    pub fn triggers(&self) -> &Vec<Trigger> {
+        self.layout.triggers()
+    }
+
+}
+
+impl FromStr for GameMap {
+    type Err = MapError;
+
+    fn from_str(level: &str) -> Result<GameMap, MapError> {
+        let layout: MapLayout = level.parse()?;
+        Ok(GameMap::new(layout))
+    }
+}
@@ -1,11 +1,26 @@
-use std::collections::HashMap;
-use std::num::NonZeroUsize;
-use quicksilver::Timer;
 use quicksilver::geom::{Vector, Rectangle, Shape, Circle};
-use quicksilver::graphics::{Image};
 use rand::Rng;
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+use rand::rngs::StdRng;
+use serde::{Serialize, Deserialize};
+
+use crate::GameObjectType;
+use crate::ai::{Ai, AiConfig, AiState, Senses};
+use crate::collision;
+use crate::cooldown::Cooldown;
+use crate::events::GameEvent;
+use crate::flow_field::FlowField;
+use crate::game_map::GameMap;
+use crate::map_layout::TILE_SIZE;
+use crate::melee::Melee;
+use crate::pickup::Loot;
+use crate::steering;
+use crate::sprite::{SpriteId, DirectionalSprites};
+use crate::weapon::{Weapon, Ammo};
+
+// share of a knockback still left after each tick
+const KNOCKBACK_DECAY: f32 = 0.8;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Right,
@@ -13,39 +28,80 @@ pub enum Direction {
     Down
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum WeaponState {
     Default,
     Attack
 }
 
-#[derive(Clone)]
+// what an enemy does once the player is in range
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Behavior {
+    // run into the player
+    Chase,
+    // close to half its range, then keep shooting
+    Shoot,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameObject {
     weapon: Option<Box<GameObject>>,
     start_position: Vector,
+    // where the last tick started, renderers draw between this and sprite.pos
+    previous_position: Vector,
     sprite: Rectangle,
     direction: Vector,
     velocity: Vector,
     acceleration: Vector,
+    // velocity gained per tick of moving
+    speed: f32,
     max_speed: f32,
-    images: HashMap<Direction, Image>,
-    image: Image,
+    images: DirectionalSprites,
+    image: SpriteId,
     collidable: bool,
     state: WeaponState,
     range: f32,
-    shoot_rate: Timer,
+    // walls a projectile bounces off before it is spent, copied from the weapon that fired it
+    ricochets: u32,
+    // enemies a projectile goes through before it is spent
+    pierce: u32,
+    // what shoot() fires, set with equip(), and what it has left to fire
+    loadout: Weapon,
+    ammo: Ammo,
+    shoot_rate: Cooldown,
+    melee: Melee,
+    // the melee swing that last hit this object, each swing hits it only once
+    struck_by: Option<u32>,
+    // extra velocity from being hit, fading out over a few ticks
+    knockback: Vector,
+    kind: GameObjectType,
+    behavior: Behavior,
+    ai: Ai,
+    health: i32,
+    max_health: i32,
+    damage: i32,
+    // what this object may drop when it dies
+    loot: Vec<Loot>,
+    // the wave that spawned this enemy, None for map placed ones
+    wave: Option<u32>,
+    // frames of invulnerability granted by each hit, and how many are left
+    invulnerability: u32,
+    invulnerable_frames: u32,
 }
 
 impl GameObject{
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         position: Vector,
-        image: &Image, 
+        image: SpriteId, 
         size: Vector, 
         velocity: Vector,
         range: f32, 
         state: WeaponState, 
-        collidable: bool
+        collidable: bool,
+        kind: GameObjectType,
     ) -> GameObject {
         
         let sprite = Rectangle::new(position, size);
@@ -53,36 +109,45 @@ impl GameObject{
         GameObject {
             weapon: None,
             start_position: position,
+            previous_position: position,
             sprite,
             direction: Vector::new(1.0, 0.0),
             velocity,
             acceleration: Vector::new(0.0, 0.0),
+            speed: 0.1,
             max_speed: 4.0,
-            image: image.clone(),
-            images: HashMap::from([
-                (Direction::Up, image.clone()),
-                (Direction::Left, image.clone()),
-                (Direction::Down, image.clone()),
-                (Direction::Right, image.clone()),
-            ]),
+            image,
+            images: DirectionalSprites::all(image),
             collidable,
             range,
+            ricochets: 0,
+            pierce: 0,
+            loadout: Weapon::default(),
+            ammo: Ammo::default(),
             state,
-            shoot_rate: Timer::time_per_second(4.0),
+            shoot_rate: Cooldown::time_per_second(4.0),
+            melee: Melee::default(),
+            struck_by: None,
+            knockback: Vector::new(0.0, 0.0),
+            kind,
+            behavior: Behavior::Chase,
+            ai: Ai::default(),
+            health: 1,
+            max_health: 1,
+            damage: 0,
+            loot: Vec::new(),
+            wave: None,
+            invulnerability: 0,
+            invulnerable_frames: 0,
         }
     }
 
     pub fn new_with_weapon(
         position: Vector, 
-        up_image: &Image,
-        left_image: &Image,
-        down_image: &Image,
-        right_image: &Image,
-        weapon_image: &Image
+        images: DirectionalSprites,
+        weapon_image: SpriteId
     ) -> GameObject {
-        let mut new_object = GameObject::new_with_direction(
-            position, up_image, left_image, down_image, right_image
-        );
+        let mut new_object = GameObject::new_with_direction(position, images);
 
         let weapon_size = Vector::new(12.0, 12.0);
         let new_weapon = GameObject::new_weapon(new_object.calculate_weapon_position(weapon_size), weapon_image);
@@ -91,43 +156,45 @@ impl GameObject{
         new_object
     }
 
-    pub fn new_random_enemy(image: &Image) -> GameObject {
-
-        let x_coord = rand::thread_rng().gen_range(200..800) as f32;
-        let y_coord = rand::thread_rng().gen_range(100..700) as f32;
+    pub fn new_enemy(position: Vector, image: SpriteId) -> GameObject {
 
-        GameObject::new(
-            Vector::new(x_coord, y_coord), 
+        let mut enemy = GameObject::new(
+            position, 
             image,
             Vector::new(12.0, 12.0),
             Vector::new(0.0,0.0),
             300.0,
             WeaponState::Attack,
             true,
-        )
+            GameObjectType::Enemy,
+        );
+        enemy.set_max_health(3);
+        // contact damage
+        enemy.set_damage(1);
+        enemy.set_invulnerability(10);
+        enemy
     }
 
-    pub fn new_weapon(position: Vector, new_image: &Image) -> GameObject {
+    pub fn new_weapon(position: Vector, new_image: SpriteId) -> GameObject {
         let size = Vector::new(12.0, 12.0);
-        GameObject::new(position, new_image, size, Vector::new(0.0, 0.0), 0.0, WeaponState::Default, false)
+        let mut weapon = GameObject::new(position, new_image, size, Vector::new(0.0, 0.0), 0.0, WeaponState::Default, false, GameObjectType::Weapon);
+        weapon.set_damage(1);
+        weapon
     }
 
-    pub fn new_floor(position: Vector, new_image: &Image) -> GameObject {
+    pub fn new_floor(position: Vector, new_image: SpriteId) -> GameObject {
         let size = Vector::new(32.0, 32.0);
-        GameObject::new(position, new_image, size, Vector::new(0.0, 0.0), 0.0, WeaponState::Default, false)
+        GameObject::new(position, new_image, size, Vector::new(0.0, 0.0), 0.0, WeaponState::Default, false, GameObjectType::Floor)
     }
 
-    pub fn new_wall(position: Vector, new_image: &Image) -> GameObject {
+    pub fn new_wall(position: Vector, new_image: SpriteId) -> GameObject {
         let size = Vector::new(32.0, 32.0);
-        GameObject::new(position, new_image, size, Vector::new(0.0,0.0), 0.0, WeaponState::Default, true)
+        GameObject::new(position, new_image, size, Vector::new(0.0,0.0), 0.0, WeaponState::Default, true, GameObjectType::Wall)
     }
 
     pub fn new_with_direction(
         position: Vector, 
-        up_image: &Image,
-        left_image: &Image,
-        down_image: &Image,
-        right_image: &Image, 
+        images: DirectionalSprites,
         ) -> GameObject {
 
         let size = Vector::new(32.0, 32.0);
@@ -136,30 +203,44 @@ impl GameObject{
         GameObject {
             weapon: None,
             start_position: position,
+            previous_position: position,
             sprite: new_sprite,
             direction: Vector::new(1.0, 0.0),
             velocity: Vector::new(0.0,0.0),
             acceleration: Vector::new(0.0, 0.0),
+            speed: 0.1,
             max_speed: 4.0,
-            images: HashMap::from([
-                (Direction::Up, up_image.clone()),
-                (Direction::Left, left_image.clone()),
-                (Direction::Down, down_image.clone()),
-                (Direction::Right, right_image.clone()),
-            ]),
-            image: right_image.clone(),
+            images,
+            image: images.right,
             collidable: true,
             state: WeaponState::Default,
             range: 300.0,
-            shoot_rate: Timer::time_per_second(2.0),
+            ricochets: 0,
+            pierce: 0,
+            loadout: Weapon::default(),
+            ammo: Ammo::default(),
+            shoot_rate: Cooldown::time_per_second(2.0),
+            melee: Melee::default(),
+            struck_by: None,
+            knockback: Vector::new(0.0, 0.0),
+            kind: GameObjectType::Player,
+            behavior: Behavior::Chase,
+            ai: Ai::default(),
+            health: 5,
+            max_health: 5,
+            damage: 0,
+            loot: Vec::new(),
+            wave: None,
+            invulnerability: 60,
+            invulnerable_frames: 0,
         }
 
     }
 
     pub fn move_up(&mut self) {
 
-        self.image = self.images[&Direction::Up].clone();
-        let new_velocity = self.velocity.y - 0.1;
+        self.image = self.images.facing(Direction::Up);
+        let new_velocity = self.velocity.y - self.speed;
         if new_velocity.abs() <= self.max_speed {
             self.velocity.y = new_velocity;
         }
@@ -167,8 +248,8 @@ impl GameObject{
 
     pub fn move_down(&mut self) {
 
-        self.image = self.images[&Direction::Down].clone();
-        let new_velocity = self.velocity.y + 0.1;
+        self.image = self.images.facing(Direction::Down);
+        let new_velocity = self.velocity.y + self.speed;
         if new_velocity.abs() <= self.max_speed {
             self.velocity.y = new_velocity;
         }
@@ -176,8 +257,8 @@ impl GameObject{
 
     pub fn move_left(&mut self) {
 
-        self.image = self.images[&Direction::Left].clone();
-        let new_velocity = self.velocity.x - 0.1;
+        self.image = self.images.facing(Direction::Left);
+        let new_velocity = self.velocity.x - self.speed;
         if new_velocity.abs() <= self.max_speed {
             self.velocity.x = new_velocity;
         }
@@ -185,39 +266,154 @@ impl GameObject{
 
     pub fn move_right(&mut self) {
 
-        self.image = self.images[&Direction::Right].clone();
-        let new_velocity = self.velocity.x + 0.1;
+        self.image = self.images.facing(Direction::Right);
+        let new_velocity = self.velocity.x + self.speed;
         if new_velocity.abs() <= self.max_speed {
             self.velocity.x = new_velocity;
         }
         
     }
 
-    pub fn shoot(&mut self, bullets: &mut Vec<GameObject>) {
-        
-        if self.shoot_rate.exhaust() >= NonZeroUsize::new(1) {
-            bullets.push(
-                GameObject::new(
+    // Fires the equipped weapon once its cooldown is up: projectiles bullets
+    // fanned out evenly across the spread, centered on where this object aims.
+    // Nothing comes out while reloading, and trying to fire an empty magazine
+    // starts a reload. Returns MagazineEmpty when this shot used the last round.
+    pub fn shoot(&mut self, bullets: &mut Vec<GameObject>) -> Option<GameEvent> {
+
+        if self.ammo.is_empty() {
+            self.ammo.reload();
+        }
+        if self.shoot_rate.is_ready() && self.ammo.can_fire() {
+            let loadout = &self.loadout;
+            let count = loadout.projectiles.max(1);
+            for index in 0..count {
+                let angle = if count == 1 {
+                    0.0
+                }
+                else {
+                    loadout.spread.to_radians() * (index as f32 / (count - 1) as f32 - 0.5)
+                };
+                let aim = rotate(self.direction, angle);
+                let mut bullet = GameObject::new(
                     self.weapon().position(),
                     self.weapon().image(),
                     self.weapon().size(),
-                    self.direction*8.0,
-                    self.range,
+                    aim * loadout.speed,
+                    loadout.range,
                     WeaponState::Attack,
                     false,
-                )
-            );
+                    GameObjectType::Projectile,
+                );
+                bullet.set_damage(self.weapon().damage());
+                bullet.set_ricochets(self.weapon().ricochets());
+                bullet.set_pierce(loadout.pierce);
+                bullets.push(bullet);
+            }
             self.shoot_rate.reset();
+            self.ammo.use_round();
+            if self.ammo.is_empty() {
+                return Some(GameEvent::MagazineEmpty { out_of_ammo: self.ammo.is_out() });
+            }
         }
+        None
+    }
+
+    // starts reloading the equipped weapon, false when there is nothing to reload
+    pub fn reload(&mut self) -> bool {
+        self.ammo.reload()
+    }
+
+    pub fn ammo(&self) -> &Ammo {
+        &self.ammo
+    }
+
+    pub fn set_ammo(&mut self, ammo: Ammo) {
+        self.ammo = ammo;
+    }
+
+    // Switches to another weapon, fully loaded. Its cooldown starts over, so
+    // switching can't be used to fire faster.
+    pub fn equip(&mut self, weapon: &Weapon) {
+        self.loadout = weapon.clone();
+        self.ammo = Ammo::full(weapon);
+        self.shoot_rate = Cooldown::time_per_second(weapon.shots_per_second);
+        if let Some(held) = self.weapon.as_mut() {
+            held.set_image(weapon.sprite);
+            held.set_size(Vector::new(weapon.size[0], weapon.size[1]));
+            held.set_damage(weapon.damage);
+            held.set_ricochets(weapon.ricochets);
+        }
+    }
+
+    pub fn loadout(&self) -> &Weapon {
+        &self.loadout
+    }
+
+    // Starts a melee swing: the weapon turns into a hitbox and sweeps an arc
+    // around the aim. Returns false while the last swing or its cooldown lasts.
+    pub fn swing(&mut self) -> bool {
+        if !self.has_weapon() || !self.melee.start() {
+            return false;
+        }
+        if let Some(weapon) = self.weapon.as_mut() {
+            weapon.set_state(WeaponState::Attack);
+        }
+        true
+    }
+
+    pub fn is_swinging(&self) -> bool {
+        self.melee.is_swinging()
+    }
+
+    // Hits the target with the swinging weapon if it touches it and this swing
+    // hasn't hit it yet, knocking it away from this object.
+    pub fn strike(&self, target: &mut GameObject) -> Option<GameEvent> {
+        if !self.is_swinging() || target.struck_by == Some(self.melee.swings()) {
+            return None;
+        }
+        if !self.weapon().sprite().overlaps_rectangle(&target.sprite()) {
+            return None;
+        }
+        let event = target.take_damage(self.melee.damage())?;
+        target.struck_by = Some(self.melee.swings());
+        let away = target.center() - self.center();
+        if away.len() > 0.0 {
+            target.knock_back(away.normalize() * self.melee.knockback());
+        }
+        Some(event)
+    }
+
+    pub fn melee(&self) -> &Melee {
+        &self.melee
+    }
+
+    pub fn set_melee(&mut self, melee: Melee) {
+        self.melee = melee;
+    }
+
+    // adds to the knockback velocity, which fades by itself
+    pub fn knock_back(&mut self, impulse: Vector) {
+        self.knockback += impulse;
+    }
+
+    pub fn knockback(&self) -> Vector {
+        self.knockback
+    }
+
+    // where the weapon points: the aim, turned by the melee swing while there is one
+    pub fn weapon_direction(&self) -> Vector {
+        rotate(self.direction, self.melee.angle())
     }
 
     pub fn calculate_weapon_position(&self, weapon_size: Vector) -> Vector {
         
+        let direction = self.weapon_direction();
+
         // compute minimum radius that subsumes this object
         let object_radius = ((self.size().x/2.0).powf(2.0) + (self.size().x/2.0).powf(2.0)).sqrt() + weapon_size.x;
         
         //compute direction magnitude
-        let direction_magnitude = (self.direction.x.powf(2.0) + self.direction.y.powf(2.0)).sqrt();
+        let direction_magnitude = (direction.x.powf(2.0) + direction.y.powf(2.0)).sqrt();
 
         //unit direction mag and object rad ratio
         // this divides by zero and then... nothing happens?
@@ -229,26 +425,150 @@ impl GameObject{
         let mut weapon_position = self.center();
         
         // displace in this objects direction by the radius
-        let displace_vector = self.direction * direction_mult;
+        let displace_vector = direction * direction_mult;
         weapon_position += displace_vector;
 
         // center the weapon
         weapon_position.x -= weapon_size.x/2.0;
         weapon_position.y -= weapon_size.y/2.0;
 
-        return weapon_position;
+        weapon_position
 
     }
 
-    pub fn patrol_for(&mut self, target: &GameObject) {
-        if self.in_range(target) {
-            self.move_towards(target.position());
+    // Runs this object's AI for one tick against the target: decides the state,
+    // then idles, wanders, chases, searches or flees accordingly.
+    pub fn patrol_for(&mut self, target: &GameObject, game_map: &GameMap, flow_field: &FlowField, rng: &mut StdRng) {
+        let senses = Senses {
+            sees_target: self.can_see(target, game_map),
+            target_position: target.center(),
+            distance: (target.center() - self.center()).len(),
+            health: self.health as f32 / self.max_health.max(1) as f32,
+        };
+
+        match self.ai.think(&senses) {
+            AiState::Idle => self.brake(),
+            AiState::Chase => self.move_around_walls_to(target.center(), game_map, flow_field),
+            AiState::Flee => {
+                self.add_steering(steering::flee(self.center(), self.velocity, target.center(), self.max_speed));
+            },
+            AiState::Wander => {
+                if !self.ai.has_route() {
+                    let route = self.wander_route(game_map, rng);
+                    self.ai.set_route(route);
+                }
+                self.follow_route();
+            },
+            AiState::Search => {
+                if !self.ai.has_route() {
+                    let route = self.ai.last_known_position()
+                        .and_then(|last_known_position| {
+                            let mut route = game_map.find_path(self.center(), last_known_position)?;
+                            // finish on the exact spot rather than its tile center
+                            route.pop();
+                            route.push(last_known_position);
+                            Some(route)
+                        })
+                        .unwrap_or_default();
+                    self.ai.set_route(route);
+                }
+                self.follow_route();
+            },
         }
-        else {
-            let x_coord = rand::thread_rng().gen_range(200..800) as f32;
-            let y_coord = rand::thread_rng().gen_range(100..700) as f32;
-            self.move_towards(Vector::new(x_coord, y_coord));
+    }
+
+    // a route to a random reachable spot within the wander radius, empty if none was found
+    fn wander_route(&self, game_map: &GameMap, rng: &mut StdRng) -> Vec<Vector> {
+        let radius = self.ai.config().wander_radius.max(1.0);
+        for _ in 0..8 {
+            let offset = Vector::new(rng.gen_range(-radius..radius), rng.gen_range(-radius..radius));
+            if let Some(mut route) = game_map.find_path(self.center(), self.center() + offset) {
+                // the first waypoint is the tile this object is on
+                route.remove(0);
+                if !route.is_empty() {
+                    return route;
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn follow_route(&mut self) {
+        // a waypoint counts as reached once this object's center is on it
+        let reach = self.size().x.min(self.size().y) / 2.0;
+        let last = self.ai.route().and_then(|route| route.last()).copied();
+        match self.ai.next_waypoint(self.center(), reach) {
+            // slow down for the end of the route, keep going through the rest
+            Some(waypoint) if Some(waypoint) == last => {
+                self.add_steering(steering::arrive(self.center(), self.velocity, waypoint, self.max_speed, TILE_SIZE));
+            },
+            Some(waypoint) => self.seek(waypoint),
+            None => self.brake(),
+        }
+    }
+
+    // steers at full speed towards a point, for this object's center
+    pub fn seek(&mut self, target: Vector) {
+        self.add_steering(steering::seek(self.center(), self.velocity, target, self.max_speed));
+    }
+
+    pub fn can_see(&self, target: &GameObject, game_map: &GameMap) -> bool {
+        self.in_range(target) && game_map.has_line_of_sight(self.center(), target.center())
+    }
+
+    pub fn last_known_position(&self) -> Option<Vector> {
+        self.ai.last_known_position()
+    }
+
+    pub fn ai(&self) -> &Ai {
+        &self.ai
+    }
+
+    pub fn ai_state(&self) -> AiState {
+        self.ai.state()
+    }
+
+    pub fn set_ai_config(&mut self, config: AiConfig) {
+        self.ai.set_config(config);
+    }
+
+    // steers towards standing still
+    pub fn brake(&mut self) {
+        self.add_steering(-self.velocity);
+    }
+
+    // adds to the acceleration applied by the next steer()
+    pub fn add_steering(&mut self, force: Vector) {
+        self.acceleration += force;
+    }
+
+    // Applies the steering added this tick, capped at this object's speed,
+    // then clears it for the next tick.
+    pub fn steer(&mut self) {
+        self.acceleration = steering::truncate(self.acceleration, self.speed);
+        self.accelerate();
+        self.acceleration = Vector::new(0.0, 0.0);
+    }
+
+    // Heads straight for the destination (a center point) when nothing is in the way,
+    // otherwise follows the flow field, which must lead to the destination, aiming
+    // for the furthest of the next few tiles it can reach directly.
+    pub fn move_around_walls_to(&mut self, destination: Vector, game_map: &GameMap, flow_field: &FlowField) {
+        let half_size = self.size() / 2.0;
+        if game_map.is_clear(&self.sprite, destination - half_size) {
+            self.seek(destination);
+            return;
         }
+
+        let waypoints = flow_field.waypoints(game_map.layout(), self.center(), 4);
+        let waypoint = waypoints.iter()
+            .skip(1)
+            .take_while(|waypoint| game_map.is_clear(&self.sprite, **waypoint - half_size))
+            .last()
+            .or(waypoints.first())
+            .copied()
+            .unwrap_or(destination);
+        self.seek(waypoint);
     }
 
     pub fn move_towards(&mut self, target_location: Vector) {
@@ -268,35 +588,94 @@ impl GameObject{
         
     }
 
-    pub fn carry_momentum(&mut self, game_map: &Vec<GameObject>) {
+    // Moves by velocity, stopping flush against the first wall in the way and sliding
+    // along it with what is left. Swept rather than stepped, so however fast an object
+    // goes it can't tunnel through a wall. Objects that aren't collidable go through everything.
+    pub fn carry_momentum(&mut self, game_map: &GameMap) {
+
+        let mut travel = self.velocity + self.knockback;
+        if !self.collidable {
+            self.sprite.pos += travel;
+        }
+        else {
+            // a wall can only stop each axis once
+            for _ in 0..2 {
+                match game_map.sweep(&self.sprite, travel) {
+                    Some(hit) => {
+                        self.sprite.pos = hit.position;
+                        travel *= 1.0 - hit.time;
+                        if hit.normal.x != 0.0 {
+                            travel.x = 0.0;
+                            self.velocity.x = 0.0;
+                            self.knockback.x = 0.0;
+                        }
+                        else {
+                            travel.y = 0.0;
+                            self.velocity.y = 0.0;
+                            self.knockback.y = 0.0;
+                        }
+                    },
+                    None => {
+                        self.sprite.pos += travel;
+                        break;
+                    },
+                }
+            }
+        }
+
+        self.knockback *= KNOCKBACK_DECAY;
+        if self.knockback.len() < 0.1 {
+            self.knockback = Vector::new(0.0, 0.0);
+        }
 
-        self.sprite.pos.x += self.velocity.x;
-        if self.check_collisions(game_map){
-            self.sprite.pos.x -= self.velocity.x;
-            self.velocity.x = 0.0;
+        if let Some(weapon_size) = self.weapon.as_ref().map(|weapon| weapon.size()) {
+            let new_weapon_position = self.calculate_weapon_position(weapon_size);
+            if let Some(weapon) = self.weapon.as_mut() {
+                weapon.set_position(new_weapon_position);
+            }
         }
+    }
 
-        self.sprite.pos.y += self.velocity.y;
-        if self.check_collisions(game_map){
-            self.sprite.pos.y -= self.velocity.y;
-            self.velocity.y = 0.0;
+    // Moves a projectile, which flies over everything but walls. A wall bounces it
+    // back while it has ricochets left, otherwise it stops there and is spent.
+    // Returns the Ricochet or Impact, None when it flew freely.
+    pub fn carry_projectile(&mut self, game_map: &GameMap) -> Option<GameEvent> {
+
+        let hit = match game_map.sweep(&self.sprite, self.velocity) {
+            Some(hit) => hit,
+            None => {
+                self.sprite.pos += self.velocity;
+                return None;
+            },
+        };
+        self.sprite.pos = hit.position;
+        // the point on the wall it hit
+        let position = self.center() - Vector::new(hit.normal.x * self.size().x, hit.normal.y * self.size().y) / 2.0;
+
+        if self.ricochets == 0 {
+            self.velocity = Vector::new(0.0, 0.0);
+            return Some(GameEvent::Impact { position });
         }
-        if self.weapon.is_some() {
-            let new_weapon_position = self.calculate_weapon_position(self.weapon().size());
-            self.weapon.as_mut().expect("no weapon carry momentum").set_position(new_weapon_position);
+        self.ricochets -= 1;
+        if hit.normal.x != 0.0 {
+            self.velocity.x = -self.velocity.x;
         }
+        else {
+            self.velocity.y = -self.velocity.y;
+        }
+        Some(GameEvent::Ricochet { position })
     }
 
-    fn check_collisions(&self, game_map: &Vec<GameObject>) -> bool{
+    // the area this object swept through this tick, from previous_position to where it is now
+    pub fn swept_sprite(&self) -> Rectangle {
+        collision::swept_bounds(&Rectangle::new(self.previous_position, self.size()), self.sprite.pos - self.previous_position)
+    }
 
-        let mut collision_detected: bool = false;
-        for map_element in game_map{
-            collision_detected = self.collides_with(map_element);
-            if collision_detected {
-                break
-            }
-        }
-        collision_detected
+    // Whether this object touched the area anywhere on its way this tick, not just
+    // where it ended up, so fast bullets can't skip over small targets.
+    pub fn passed_through(&self, area: &Rectangle) -> bool {
+        let start = Rectangle::new(self.previous_position, self.size());
+        collision::passes_through(&start, self.sprite.pos - self.previous_position, area)
     }
 
     pub fn update_direction(&mut self, new_direction: Direction) {
@@ -336,6 +715,14 @@ impl GameObject{
 
     }
 
+    // points this object, and so its weapon, straight at the target
+    pub fn aim_at(&mut self, target: Vector) {
+        let offset = target - self.center();
+        if offset.len() > 0.0 {
+            self.direction = offset.normalize();
+        }
+    }
+
     pub fn got_shot(&self, bullets: &Vec<GameObject>) -> bool {
 
         for bullet in bullets{
@@ -343,19 +730,19 @@ impl GameObject{
                 return true;
             }
         }
-        return false;
+        false
 
     }
 
     pub fn out_of_range(&self) -> bool {
         let range_radius = Circle::new(self.start_position, self.range);
-        return !self.sprite().overlaps_circle(&range_radius);
+        !self.sprite().overlaps_circle(&range_radius)
     }
 
     pub fn in_range(&self, target: &GameObject) -> bool {
         
         let range_radius = Circle::new(self.position(), self.range);
-        return target.sprite().overlaps_circle(&range_radius);
+        target.sprite().overlaps_circle(&range_radius)
 
     }
 
@@ -364,7 +751,7 @@ impl GameObject{
     }
 
     pub fn weapon(&self) -> &GameObject {
-        &self.weapon.as_ref().expect("No weapon to get")
+        self.weapon.as_ref().expect("No weapon to get")
     }
 
     pub fn accelerate(&mut self) {
@@ -381,6 +768,14 @@ impl GameObject{
         
     }
 
+    pub fn velocity(&self) -> Vector {
+        self.velocity
+    }
+
+    pub fn set_velocity(&mut self, new_velocity: Vector) {
+        self.velocity = new_velocity;
+    }
+
     pub fn set_acceleration(&mut self, tilt: Vector) {
         
         self.acceleration = tilt*0.1;
@@ -388,14 +783,14 @@ impl GameObject{
     }
 
     pub fn acceleration(&self) -> Vector {
-        return self.acceleration;
+        self.acceleration
     }
 
-    pub fn image(&self) -> &Image {
-        &self.image
+    pub fn image(&self) -> SpriteId {
+        self.image
     }
 
-    pub fn set_image(&mut self, new_image: Image) {
+    pub fn set_image(&mut self, new_image: SpriteId) {
         self.image = new_image;
     }
 
@@ -407,17 +802,51 @@ impl GameObject{
         self.sprite.size
     }
 
+    pub fn set_size(&mut self, new_size: Vector) {
+        self.sprite.size = new_size;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, new_speed: f32) {
+        self.speed = new_speed;
+    }
+
+    pub fn max_speed(&self) -> f32 {
+        self.max_speed
+    }
+
+    pub fn set_max_speed(&mut self, new_max_speed: f32) {
+        self.max_speed = new_max_speed;
+    }
+
+    pub fn behavior(&self) -> Behavior {
+        self.behavior
+    }
+
+    pub fn set_behavior(&mut self, new_behavior: Behavior) {
+        self.behavior = new_behavior;
+    }
+
+    pub fn has_weapon(&self) -> bool {
+        self.weapon.is_some()
+    }
+
+    pub fn set_shoot_rate(&mut self, times_per_second: f32) {
+        self.shoot_rate = Cooldown::time_per_second(times_per_second);
+    }
+
     pub fn position(&self) -> Vector {
         self.sprite.pos
     }
 
     pub fn center(&self) -> Vector {
 
-        let center = Vector::new(
+        Vector::new(
             self.position().x + (self.size().x/2.0), 
-            self.position().y + (self.size().y/2.0));
-
-        return center;
+            self.position().y + (self.size().y/2.0))
 
     }
 
@@ -425,6 +854,10 @@ impl GameObject{
         self.collidable
     }
 
+    pub fn set_collidable(&mut self, collidable: bool) {
+        self.collidable = collidable;
+    }
+
     pub fn collides_with(&self, other_object: &GameObject) -> bool {
         if other_object.is_collidable() && self.is_collidable() {
             return self.sprite.overlaps_rectangle(&other_object.sprite());
@@ -436,6 +869,28 @@ impl GameObject{
         self.sprite.pos = new_position;
     }
 
+    // call at the start of every tick, before anything moves
+    pub fn remember_position(&mut self) {
+        self.previous_position = self.sprite.pos;
+        if let Some(weapon) = self.weapon.as_mut() {
+            weapon.remember_position();
+        }
+    }
+
+    pub fn previous_position(&self) -> Vector {
+        self.previous_position
+    }
+
+    // alpha is how far the renderer is between the last tick (0.0) and the next (1.0)
+    pub fn interpolated_sprite(&self, alpha: f32) -> Rectangle {
+        let position = self.previous_position + (self.sprite.pos - self.previous_position) * alpha;
+        Rectangle::new(position, self.sprite.size)
+    }
+
+    pub fn interpolated_center(&self, alpha: f32) -> Vector {
+        self.interpolated_sprite(alpha).center()
+    }
+
     pub fn range(&self) -> f32 {
         self.range
     }
@@ -444,6 +899,116 @@ impl GameObject{
         self.range = new_range;
     }
 
+    pub fn ricochets(&self) -> u32 {
+        self.ricochets
+    }
+
+    pub fn set_ricochets(&mut self, new_ricochets: u32) {
+        self.ricochets = new_ricochets;
+    }
+
+    pub fn pierce(&self) -> u32 {
+        self.pierce
+    }
+
+    pub fn set_pierce(&mut self, new_pierce: u32) {
+        self.pierce = new_pierce;
+    }
+
+    pub fn kind(&self) -> GameObjectType {
+        self.kind
+    }
+
+    pub fn health(&self) -> i32 {
+        self.health
+    }
+
+    pub fn max_health(&self) -> i32 {
+        self.max_health
+    }
+
+    // also heals to full
+    pub fn set_max_health(&mut self, new_max_health: i32) {
+        self.max_health = new_max_health;
+        self.health = new_max_health;
+    }
+
+    // up to max health
+    pub fn heal(&mut self, amount: i32) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0
+    }
+
+    pub fn damage(&self) -> i32 {
+        self.damage
+    }
+
+    pub fn set_damage(&mut self, new_damage: i32) {
+        self.damage = new_damage;
+    }
+
+    pub fn loot(&self) -> &Vec<Loot> {
+        &self.loot
+    }
+
+    pub fn set_loot(&mut self, new_loot: Vec<Loot>) {
+        self.loot = new_loot;
+    }
+
+    pub fn wave(&self) -> Option<u32> {
+        self.wave
+    }
+
+    pub fn set_wave(&mut self, new_wave: Option<u32>) {
+        self.wave = new_wave;
+    }
+
+    pub fn set_invulnerability(&mut self, frames: u32) {
+        self.invulnerability = frames;
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_frames > 0
+    }
+
+    pub fn invulnerable_frames(&self) -> u32 {
+        self.invulnerable_frames
+    }
+
+    // advances invulnerability, the shoot cooldown, reloading and the melee swing,
+    // call once per tick
+    pub fn update_timers(&mut self) {
+        self.invulnerable_frames = self.invulnerable_frames.saturating_sub(1);
+        self.shoot_rate.tick();
+        self.ammo.tick();
+        self.melee.tick();
+        if !self.melee.is_swinging() {
+            if let Some(weapon) = self.weapon.as_mut() {
+                weapon.set_state(WeaponState::Default);
+            }
+        }
+    }
+
+    // Returns what happened, or None when the hit was ignored
+    // because this object is invulnerable or already dead.
+    pub fn take_damage(&mut self, amount: i32) -> Option<GameEvent> {
+
+        if self.is_dead() || self.is_invulnerable() {
+            return None;
+        }
+
+        self.health -= amount;
+        self.invulnerable_frames = self.invulnerability;
+
+        if self.is_dead() {
+            return Some(GameEvent::Died { kind: self.kind, position: self.center() });
+        }
+        Some(GameEvent::Damaged { kind: self.kind, amount, position: self.center() })
+    }
+
     pub fn state(&self) -> WeaponState {
         self.state
     }
@@ -452,4 +1017,10 @@ impl GameObject{
         self.state = new_state;
     }
 
-}
\ No newline at end of file
+}
+
+// v turned by angle radians, clockwise on screen
+fn rotate(v: Vector, angle: f32) -> Vector {
+    let (sin, cos) = angle.sin_cos();
+    Vector::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
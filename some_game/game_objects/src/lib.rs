@@ -1,12 +1,35 @@
 pub mod game_object;
+pub mod ai;
+pub mod archetype;
 pub mod game_map;
+pub mod map_layout;
+pub mod tiled;
+pub mod dungeon;
+pub mod pathfinding;
+pub mod flow_field;
+pub mod spatial_hash;
+pub mod collision;
+pub mod steering;
+pub mod events;
+pub mod cooldown;
+pub mod weapon;
+pub mod melee;
+pub mod pickup;
+pub mod waves;
+pub mod world;
+pub mod replay;
+pub mod sprite;
 
 
-#[derive(PartialEq, Copy, Clone)]
+use serde::{Serialize, Deserialize};
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum GameObjectType {
     Enemy,
     Player,
     Weapon,
+    Projectile,
     Wall,
     Floor,
+    Pickup,
 }
\ No newline at end of file
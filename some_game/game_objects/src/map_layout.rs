@@ -0,0 +1,356 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use quicksilver::geom::{Vector, Rectangle};
+
+use crate::GameObjectType;
+use crate::collision::{self, Hit};
+use crate::pickup::Item;
+
+pub const TILE_SIZE: f32 = 32.0;
+
+// level file characters
+const WALL: char = '#';
+const FLOOR: char = '.';
+const PLAYER_SPAWN: char = 'P';
+const ENEMY_SPAWN: char = 'E';
+const HEALTH: char = 'H';
+const AMMO: char = 'A';
+const GEM: char = 'G';
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Tile {
+    pub kind: GameObjectType,
+    pub collidable: bool,
+}
+
+impl Tile {
+
+    pub fn wall() -> Tile {
+        Tile { kind: GameObjectType::Wall, collidable: true }
+    }
+
+    pub fn floor() -> Tile {
+        Tile { kind: GameObjectType::Floor, collidable: false }
+    }
+
+}
+
+#[derive(Clone)]
+pub struct Trigger {
+    pub name: String,
+    pub area: Rectangle,
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    Tiled(String),
+    Empty,
+    UnknownTile { line: usize, column: usize, found: char },
+    RaggedRow { line: usize, expected: usize, found: usize },
+    DuplicatePlayerSpawn { line: usize, column: usize },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "could not read level: {}", err),
+            MapError::Tiled(message) => write!(f, "invalid Tiled map: {}", message),
+            MapError::Empty => write!(f, "level has no rows"),
+            MapError::UnknownTile { line, column, found } => {
+                write!(f, "line {}, column {}: unknown tile '{}'", line, column, found)
+            },
+            MapError::RaggedRow { line, expected, found } => {
+                write!(f, "line {}: expected {} columns, found {}", line, expected, found)
+            },
+            MapError::DuplicatePlayerSpawn { line, column } => {
+                write!(f, "line {}, column {}: player spawn already placed", line, column)
+            },
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl From<io::Error> for MapError {
+    fn from(err: io::Error) -> MapError {
+        MapError::Io(err)
+    }
+}
+
+// Tile grid plus spawn markers, independent of any images.
+// Cells are (column, row) pairs; row 0 is the top of the map.
+#[derive(Clone)]
+pub struct MapLayout {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+    player_spawn: Option<(usize, usize)>,
+    enemy_spawns: Vec<(usize, usize)>,
+    // items lying on the map at the start
+    pickups: Vec<((usize, usize), Item)>,
+    triggers: Vec<Trigger>,
+}
+
+impl MapLayout {
+
+    pub fn new(width: usize, height: usize, fill: Tile) -> MapLayout {
+        MapLayout {
+            width,
+            height,
+            tiles: vec![fill; width * height],
+            player_spawn: None,
+            enemy_spawns: Vec::new(),
+            pickups: Vec::new(),
+            triggers: Vec::new(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<MapLayout, MapError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn tiles(&self) -> &Vec<Tile> {
+        &self.tiles
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> Option<&Tile> {
+        if x < self.width && y < self.height {
+            return self.tiles.get(y * self.width + x);
+        }
+        None
+    }
+
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: Tile) {
+        if x < self.width && y < self.height {
+            self.tiles[y * self.width + x] = tile;
+        }
+    }
+
+    pub fn player_spawn(&self) -> Option<(usize, usize)> {
+        self.player_spawn
+    }
+
+    pub fn set_player_spawn(&mut self, cell: (usize, usize)) {
+        self.player_spawn = Some(cell);
+    }
+
+    pub fn enemy_spawns(&self) -> &Vec<(usize, usize)> {
+        &self.enemy_spawns
+    }
+
+    pub fn add_enemy_spawn(&mut self, cell: (usize, usize)) {
+        self.enemy_spawns.push(cell);
+    }
+
+    pub fn pickups(&self) -> &Vec<((usize, usize), Item)> {
+        &self.pickups
+    }
+
+    pub fn add_pickup(&mut self, cell: (usize, usize), item: Item) {
+        self.pickups.push((cell, item));
+    }
+
+    pub fn triggers(&self) -> &Vec<Trigger> {
+        &self.triggers
+    }
+
+    pub fn add_trigger(&mut self, trigger: Trigger) {
+        self.triggers.push(trigger);
+    }
+
+    // Cells a rectangle overlaps, clipped to the map. Only these tiles can touch it,
+    // so collision checks don't need to scan the whole map.
+    pub fn cells_overlapping(&self, area: &Rectangle) -> impl Iterator<Item = (usize, usize)> {
+        let (first_x, end_x) = MapLayout::cell_span(area.pos.x, area.size.x, self.width);
+        let (first_y, end_y) = MapLayout::cell_span(area.pos.y, area.size.y, self.height);
+        (first_y..end_y).flat_map(move |y| (first_x..end_x).map(move |x| (x, y)))
+    }
+
+    pub fn collides(&self, area: &Rectangle) -> bool {
+        self.cells_overlapping(area)
+            .any(|(x, y)| self.tiles[y * self.width + x].collidable)
+    }
+
+    // The first wall a rectangle runs into moving by travel, None when the way is clear.
+    // Walls it already overlaps are ignored.
+    pub fn sweep(&self, area: &Rectangle, travel: Vector) -> Option<Hit> {
+        self.cells_overlapping(&collision::swept_bounds(area, travel))
+            .filter(|(x, y)| self.tiles[y * self.width + x].collidable)
+            .filter_map(|cell| {
+                let wall = Rectangle::new(MapLayout::cell_position(cell), Vector::new(TILE_SIZE, TILE_SIZE));
+                collision::sweep(area, travel, &wall)
+            })
+            .min_by(|first, second| first.time.total_cmp(&second.time))
+    }
+
+    // half open range of cells covered along one axis, matching the strict
+    // inequalities quicksilver uses for rectangle overlap
+    fn cell_span(start: f32, length: f32, cells: usize) -> (usize, usize) {
+        let first = (start / TILE_SIZE).floor().max(0.0) as usize;
+        let end = ((start + length) / TILE_SIZE).ceil().max(0.0) as usize;
+        (first.min(cells), end.min(cells))
+    }
+
+    // top left corner of a cell in world coordinates
+    pub fn cell_position(cell: (usize, usize)) -> Vector {
+        Vector::new(cell.0 as f32 * TILE_SIZE, cell.1 as f32 * TILE_SIZE)
+    }
+
+    pub fn cell_center(cell: (usize, usize)) -> Vector {
+        MapLayout::cell_position(cell) + Vector::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0)
+    }
+
+    // the cell a world position falls in, None off the map
+    pub fn cell_at(&self, position: Vector) -> Option<(usize, usize)> {
+        if position.x < 0.0 || position.y < 0.0 {
+            return None;
+        }
+        let cell = ((position.x / TILE_SIZE) as usize, (position.y / TILE_SIZE) as usize);
+        if cell.0 < self.width && cell.1 < self.height {
+            return Some(cell);
+        }
+        None
+    }
+
+    // Walks every cell the segment passes through, in order (grid DDA), and returns
+    // the first collidable one, or None when the line is clear. A line passing
+    // exactly through a corner is blocked if either cell beside the corner is.
+    // Off the map counts as open.
+    pub fn first_wall_on_line(&self, from: Vector, to: Vector) -> Option<(usize, usize)> {
+        let blocked = |x: i64, y: i64| {
+            x >= 0 && y >= 0 && self.tile(x as usize, y as usize).is_some_and(|tile| tile.collidable)
+        };
+        let cell_of = |position: Vector| ((position.x / TILE_SIZE).floor() as i64, (position.y / TILE_SIZE).floor() as i64);
+
+        let (mut x, mut y) = cell_of(from);
+        let end = cell_of(to);
+        let travel = to - from;
+        let step_x = if travel.x > 0.0 { 1 } else { -1 };
+        let step_y = if travel.y > 0.0 { 1 } else { -1 };
+
+        // how far along the line (0 to 1) the next vertical / horizontal cell edge is,
+        // and how far apart those edges are
+        let first_edge = |start: f32, cell: i64, step: i64, length: f32| {
+            if length == 0.0 {
+                return f32::INFINITY;
+            }
+            let edge = (cell + if step > 0 { 1 } else { 0 }) as f32 * TILE_SIZE;
+            (edge - start) / length
+        };
+        let mut next_x = first_edge(from.x, x, step_x, travel.x);
+        let mut next_y = first_edge(from.y, y, step_y, travel.y);
+        let delta_x = if travel.x == 0.0 { f32::INFINITY } else { TILE_SIZE / travel.x.abs() };
+        let delta_y = if travel.y == 0.0 { f32::INFINITY } else { TILE_SIZE / travel.y.abs() };
+
+        let cells = (end.0 - x).abs() + (end.1 - y).abs();
+        for _ in 0..=cells {
+            if blocked(x, y) {
+                return Some((x as usize, y as usize));
+            }
+            if (x, y) == end {
+                break;
+            }
+            if next_x < next_y {
+                x += step_x;
+                next_x += delta_x;
+            }
+            else if next_y < next_x {
+                y += step_y;
+                next_y += delta_y;
+            }
+            else {
+                for (side_x, side_y) in [(x + step_x, y), (x, y + step_y)] {
+                    if blocked(side_x, side_y) {
+                        return Some((side_x as usize, side_y as usize));
+                    }
+                }
+                x += step_x;
+                y += step_y;
+                next_x += delta_x;
+                next_y += delta_y;
+            }
+        }
+        None
+    }
+
+    // inside the map and not collidable
+    pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        self.tile(x, y).is_some_and(|tile| !tile.collidable)
+    }
+
+}
+
+impl FromStr for MapLayout {
+    type Err = MapError;
+
+    fn from_str(level: &str) -> Result<MapLayout, MapError> {
+
+        let rows: Vec<&str> = level
+            .lines()
+            .map(|row| row.trim_end_matches('\r'))
+            .collect();
+
+        // ignore blank lines at the end of the file
+        let row_count = rows.iter().rposition(|row| !row.is_empty()).map_or(0, |last| last + 1);
+        if row_count == 0 {
+            return Err(MapError::Empty);
+        }
+
+        let width = rows[0].chars().count();
+        let mut layout = MapLayout::new(width, row_count, Tile::floor());
+
+        for (y, row) in rows[..row_count].iter().enumerate() {
+            let line = y + 1;
+
+            let found = row.chars().count();
+            if found != width {
+                return Err(MapError::RaggedRow { line, expected: width, found });
+            }
+
+            for (x, symbol) in row.chars().enumerate() {
+                let column = x + 1;
+                let tile = match symbol {
+                    WALL => Tile::wall(),
+                    FLOOR => Tile::floor(),
+                    PLAYER_SPAWN => {
+                        if layout.player_spawn.is_some() {
+                            return Err(MapError::DuplicatePlayerSpawn { line, column });
+                        }
+                        layout.set_player_spawn((x, y));
+                        Tile::floor()
+                    },
+                    ENEMY_SPAWN => {
+                        layout.add_enemy_spawn((x, y));
+                        Tile::floor()
+                    },
+                    HEALTH | AMMO | GEM => {
+                        let item = match symbol {
+                            HEALTH => Item::Health { amount: 1 },
+                            AMMO => Item::Ammo { rounds: 10 },
+                            _ => Item::Gem { score: 1 },
+                        };
+                        layout.add_pickup((x, y), item);
+                        Tile::floor()
+                    },
+                    found => return Err(MapError::UnknownTile { line, column, found }),
+                };
+                layout.set_tile(x, y, tile);
+            }
+        }
+
+        Ok(layout)
+    }
+}
@@ -0,0 +1,88 @@
+use serde::{Serialize, Deserialize};
+
+use crate::cooldown::Cooldown;
+
+// how long a swing lasts, in ticks, and how wide it sweeps around the aim, in degrees
+pub const SWING_FRAMES: u32 = 12;
+pub const SWING_ARC: f32 = 120.0;
+
+// A melee swing: for SWING_FRAMES the weapon sweeps across SWING_ARC around
+// where its owner aims and hurts whatever it touches on the way.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Melee {
+    cooldown: Cooldown,
+    damage: i32,
+    // speed, in pixels per tick, a hit sends the target flying away at
+    knockback: f32,
+    frames_left: u32,
+    // counts every swing, so each one can hit a target only once
+    swings: u32,
+}
+
+impl Melee {
+
+    pub fn new(swings_per_second: f32, damage: i32, knockback: f32) -> Melee {
+        Melee {
+            cooldown: Cooldown::time_per_second(swings_per_second),
+            damage,
+            knockback,
+            frames_left: 0,
+            swings: 0,
+        }
+    }
+
+    // Starts a swing, unless one is still going or the cooldown isn't up.
+    // Returns whether it did.
+    pub fn start(&mut self) -> bool {
+        if self.is_swinging() || !self.cooldown.is_ready() {
+            return false;
+        }
+        self.frames_left = SWING_FRAMES;
+        self.swings = self.swings.wrapping_add(1);
+        self.cooldown.reset();
+        true
+    }
+
+    // call once per tick
+    pub fn tick(&mut self) {
+        self.frames_left = self.frames_left.saturating_sub(1);
+        // the cooldown runs from the end of the swing
+        if !self.is_swinging() {
+            self.cooldown.tick();
+        }
+    }
+
+    pub fn is_swinging(&self) -> bool {
+        self.frames_left > 0
+    }
+
+    // how far the weapon is turned away from the aim, in radians, running
+    // from one edge of the arc to the other over the swing
+    pub fn angle(&self) -> f32 {
+        if !self.is_swinging() {
+            return 0.0;
+        }
+        let progress = (SWING_FRAMES - self.frames_left) as f32 / SWING_FRAMES as f32;
+        SWING_ARC.to_radians() * (progress - 0.5)
+    }
+
+    // which swing this is
+    pub fn swings(&self) -> u32 {
+        self.swings
+    }
+
+    pub fn damage(&self) -> i32 {
+        self.damage
+    }
+
+    pub fn knockback(&self) -> f32 {
+        self.knockback
+    }
+
+}
+
+impl Default for Melee {
+    fn default() -> Melee {
+        Melee::new(2.0, 2, 6.0)
+    }
+}
@@ -0,0 +1,93 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::map_layout::MapLayout;
+
+// step costs, roughly 1 and sqrt(2) scaled to stay in integers
+pub const STRAIGHT: u32 = 10;
+pub const DIAGONAL: u32 = 14;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (0, -1), (1, 0), (0, 1), (-1, 0),
+    (1, -1), (1, 1), (-1, 1), (-1, -1),
+];
+
+// A* over the layout's walkable cells, moving in 8 directions.
+// A diagonal step is only allowed when both cells it squeezes between are
+// walkable too, so paths never cut a wall's corner.
+// Returns every cell from start to goal inclusive, or None when there is no way through.
+pub fn find_path(layout: &MapLayout, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    if !layout.is_walkable(start.0, start.1) || !layout.is_walkable(goal.0, goal.1) {
+        return None;
+    }
+
+    let width = layout.width();
+    let index = |cell: (usize, usize)| cell.1 * width + cell.0;
+
+    let mut cost = vec![u32::MAX; width * layout.height()];
+    let mut came_from: Vec<Option<(usize, usize)>> = vec![None; width * layout.height()];
+    // ties go to the higher cost, which is the one closer to the goal
+    let mut open = BinaryHeap::new();
+
+    cost[index(start)] = 0;
+    open.push((Reverse(heuristic(start, goal)), 0, start));
+
+    while let Some((_, cell_cost, cell)) = open.pop() {
+        if cell == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from[index(current)] {
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // stale entry, this cell was reached cheaper since
+        if cell_cost > cost[index(cell)] {
+            continue;
+        }
+
+        for (next, step_cost) in neighbours(layout, cell) {
+            let next_cost = cell_cost + step_cost;
+            if next_cost < cost[index(next)] {
+                cost[index(next)] = next_cost;
+                came_from[index(next)] = Some(cell);
+                open.push((Reverse(next_cost + heuristic(next, goal)), next_cost, next));
+            }
+        }
+    }
+
+    None
+}
+
+// Walkable cells one step away and what the step costs. Diagonals need both
+// cells they squeeze between to be walkable, so nothing cuts a wall's corner.
+pub fn neighbours(layout: &MapLayout, cell: (usize, usize)) -> impl Iterator<Item = ((usize, usize), u32)> + '_ {
+    NEIGHBOURS.iter().filter_map(move |(dx, dy)| {
+        let next = step(layout, cell, *dx, *dy)?;
+        if *dx != 0 && *dy != 0 {
+            step(layout, cell, *dx, 0)?;
+            step(layout, cell, 0, *dy)?;
+            return Some((next, DIAGONAL));
+        }
+        Some((next, STRAIGHT))
+    })
+}
+
+// the walkable neighbour in that direction, if any
+fn step(layout: &MapLayout, cell: (usize, usize), dx: i32, dy: i32) -> Option<(usize, usize)> {
+    let x = cell.0.checked_add_signed(dx as isize)?;
+    let y = cell.1.checked_add_signed(dy as isize)?;
+    if layout.is_walkable(x, y) {
+        return Some((x, y));
+    }
+    None
+}
+
+// octile distance, exact on an open grid so A* stays optimal
+fn heuristic(from: (usize, usize), to: (usize, usize)) -> u32 {
+    let dx = from.0.abs_diff(to.0) as u32;
+    let dy = from.1.abs_diff(to.1) as u32;
+    STRAIGHT * dx.max(dy) + (DIAGONAL - STRAIGHT) * dx.min(dy)
+}
@@ -0,0 +1,134 @@
+use std::str::FromStr;
+
+use quicksilver::geom::Vector;
+use serde::{Serialize, Deserialize};
+
+use crate::GameObjectType;
+use crate::game_object::{GameObject, WeaponState};
+use crate::sprite::SpriteId;
+
+// What a pickup gives the player. In TOML loot tables the variant goes in an
+// "item" key next to its field, e.g. item = "health", amount = 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "item", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Item {
+    // heals, left lying while the player is at full health
+    Health { amount: i32 },
+    // spare rounds for the weapon in hand
+    Ammo { rounds: u32 },
+    // unlocks a weapon and switches to it, or gives its ammo if already owned
+    Weapon { name: String },
+    // adds to the score
+    Gem { score: u32 },
+}
+
+// Item without its contents, for events
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum PickupKind {
+    Health,
+    Ammo,
+    Weapon,
+    Gem,
+}
+
+impl Item {
+
+    pub fn kind(&self) -> PickupKind {
+        match self {
+            Item::Health { .. } => PickupKind::Health,
+            Item::Ammo { .. } => PickupKind::Ammo,
+            Item::Weapon { .. } => PickupKind::Weapon,
+            Item::Gem { .. } => PickupKind::Gem,
+        }
+    }
+
+}
+
+// Written "health 2", "ammo 10", "weapon shotgun" or "gem 5", as in Tiled object names.
+// The number can be left out for 1 (10 for ammo).
+impl FromStr for Item {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Item, String> {
+        let mut words = text.split_whitespace();
+        let kind = words.next().unwrap_or_default();
+        let value = words.next();
+        let invalid = || format!("invalid pickup \"{}\"", text);
+        let number = |default: u32| value.map_or(Ok(default), |value| value.parse::<u32>().map_err(|_| invalid()));
+        let item = match kind {
+            "health" => {
+                // parsed as i32 itself, a u32 cast could wrap to a negative heal
+                let amount = value.map_or(Ok(1), |value| value.parse::<i32>().map_err(|_| invalid()))?;
+                if amount <= 0 {
+                    return Err(invalid());
+                }
+                Item::Health { amount }
+            },
+            "ammo" => Item::Ammo { rounds: number(10)? },
+            "gem" => Item::Gem { score: number(1)? },
+            "weapon" => Item::Weapon { name: value.ok_or_else(invalid)?.to_string() },
+            _ => return Err(invalid()),
+        };
+        if words.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(item)
+    }
+}
+
+// One entry of an enemy's loot table: dropped on death with this chance, 0 to 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Loot {
+    pub chance: f32,
+    #[serde(flatten)]
+    pub item: Item,
+}
+
+// An item lying on the map until the player walks over it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pickup {
+    object: GameObject,
+    item: Item,
+}
+
+impl Pickup {
+
+    pub fn new(center: Vector, item: Item) -> Pickup {
+        let size = Vector::new(10.0, 10.0);
+        let object = GameObject::new(
+            center - size / 2.0,
+            SpriteId::Circle,
+            size,
+            Vector::new(0.0, 0.0),
+            0.0,
+            WeaponState::Default,
+            false,
+            GameObjectType::Pickup,
+        );
+        Pickup { object, item }
+    }
+
+    pub fn object(&self) -> &GameObject {
+        &self.object
+    }
+
+    pub fn item(&self) -> &Item {
+        &self.item
+    }
+
+    pub fn center(&self) -> Vector {
+        self.object.center()
+    }
+
+    pub fn remember_position(&mut self) {
+        self.object.remember_position();
+    }
+
+    // moves up to speed pixels towards a point, without overshooting it
+    pub fn pull_towards(&mut self, target: Vector, speed: f32) {
+        let offset = target - self.center();
+        let step = if offset.len() > speed { offset.normalize() * speed } else { offset };
+        self.object.set_position(self.object.position() + step);
+    }
+
+}
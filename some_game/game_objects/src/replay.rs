@@ -0,0 +1,268 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use quicksilver::geom::Vector;
+
+use crate::events::GameEvent;
+use crate::world::{World, InputSnapshot};
+
+// Replays are a seed, the level they were played on and the input of every tick.
+// With the world's seeded rng that is enough to play a run back exactly, as long
+// as the game data and the simulation are the ones it was recorded with: the
+// data is checked by its hash, the simulation only by the version.
+//
+// File layout, little endian:
+//   "SGRP", version u8, seed u64, data hash u64, level length u32, level utf8, run count u32
+//   then per run of identical ticks: flags u16, repeat u16
+//   and, when the sticks flag is set, left x, left y, right x, right y as f32
+const MAGIC: &[u8; 4] = b"SGRP";
+// bump whenever the layout above or the simulation changes, older replays
+// would not play back the same
+const VERSION: u8 = 3;
+
+const MOVE_UP: u16 = 1 << 0;
+const MOVE_DOWN: u16 = 1 << 1;
+const MOVE_LEFT: u16 = 1 << 2;
+const MOVE_RIGHT: u16 = 1 << 3;
+const AIM_UP: u16 = 1 << 4;
+const AIM_DOWN: u16 = 1 << 5;
+const AIM_LEFT: u16 = 1 << 6;
+const AIM_RIGHT: u16 = 1 << 7;
+const SHOOT: u16 = 1 << 8;
+const STICKS: u16 = 1 << 9;
+const NEXT_WEAPON: u16 = 1 << 10;
+const PREVIOUS_WEAPON: u16 = 1 << 11;
+const MELEE: u16 = 1 << 12;
+const RELOAD: u16 = 1 << 13;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not read replay: {}", err),
+            ReplayError::Invalid(message) => write!(f, "invalid replay: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> ReplayError {
+        ReplayError::Io(err)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Replay {
+    seed: u64,
+    data: u64,
+    level: String,
+    inputs: Vec<InputSnapshot>,
+}
+
+impl Replay {
+
+    // data is the data_hash of the files the world was built from
+    pub fn new(seed: u64, level: &str, data: u64) -> Replay {
+        Replay {
+            seed,
+            data,
+            level: level.to_string(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        Replay::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    // call once per world tick with the input that tick was given
+    pub fn record(&mut self, input: &InputSnapshot) {
+        self.inputs.push(*input);
+    }
+
+    // ticks the world through every recorded input, for headless playback
+    pub fn play(&self, world: &mut World) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for input in self.inputs.iter() {
+            events.extend(world.tick(input));
+        }
+        events
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn data(&self) -> u64 {
+        self.data
+    }
+
+    pub fn level(&self) -> &str {
+        &self.level
+    }
+
+    pub fn inputs(&self) -> &Vec<InputSnapshot> {
+        &self.inputs
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.data.to_le_bytes());
+        bytes.extend_from_slice(&(self.level.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.level.as_bytes());
+
+        // most ticks look like the one before, so store runs of them
+        let mut runs: Vec<(InputSnapshot, u16)> = Vec::new();
+        for input in self.inputs.iter() {
+            match runs.last_mut() {
+                Some((last, repeat)) if last == input && *repeat < u16::MAX => *repeat += 1,
+                _ => runs.push((*input, 1)),
+            }
+        }
+
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (input, repeat) in runs {
+            bytes.extend_from_slice(&flags(&input).to_le_bytes());
+            bytes.extend_from_slice(&repeat.to_le_bytes());
+            if let Some((left, right)) = input.sticks {
+                for value in [left.x, left.y, right.x, right.y] {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err(ReplayError::Invalid(String::from("not a replay file")));
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(ReplayError::Invalid(format!("unsupported version {}", version)));
+        }
+
+        let seed = u64::from_le_bytes(reader.array()?);
+        let data = u64::from_le_bytes(reader.array()?);
+        let level_length = u32::from_le_bytes(reader.array()?) as usize;
+        let level = String::from_utf8(reader.take(level_length)?.to_vec())
+            .map_err(|_| ReplayError::Invalid(String::from("level name is not utf8")))?;
+
+        let mut replay = Replay::new(seed, &level, data);
+        let runs = u32::from_le_bytes(reader.array()?);
+        for _ in 0..runs {
+            let flags = u16::from_le_bytes(reader.array()?);
+            let repeat = u16::from_le_bytes(reader.array()?);
+            let sticks = if flags & STICKS != 0 {
+                let mut values = [0.0; 4];
+                for value in values.iter_mut() {
+                    *value = f32::from_le_bytes(reader.array()?);
+                }
+                Some((Vector::new(values[0], values[1]), Vector::new(values[2], values[3])))
+            }
+            else {
+                None
+            };
+            let input = InputSnapshot {
+                move_up: flags & MOVE_UP != 0,
+                move_down: flags & MOVE_DOWN != 0,
+                move_left: flags & MOVE_LEFT != 0,
+                move_right: flags & MOVE_RIGHT != 0,
+                aim_up: flags & AIM_UP != 0,
+                aim_down: flags & AIM_DOWN != 0,
+                aim_left: flags & AIM_LEFT != 0,
+                aim_right: flags & AIM_RIGHT != 0,
+                shoot: flags & SHOOT != 0,
+                melee: flags & MELEE != 0,
+                reload: flags & RELOAD != 0,
+                next_weapon: flags & NEXT_WEAPON != 0,
+                previous_weapon: flags & PREVIOUS_WEAPON != 0,
+                sticks,
+            };
+            replay.inputs.extend(std::iter::repeat_n(input, repeat as usize));
+        }
+
+        if reader.position != bytes.len() {
+            return Err(ReplayError::Invalid(String::from("trailing bytes")));
+        }
+        Ok(replay)
+    }
+
+}
+
+// FNV-1a over the text of every file the world is built from, the level and the
+// enemies, weapons and waves. Unlike std's hashers it is the same on every build.
+pub fn data_hash(files: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for file in files {
+        // the length keeps "ab" + "c" apart from "a" + "bc"
+        for byte in (file.len() as u64).to_le_bytes().iter().chain(file.as_bytes()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn flags(input: &InputSnapshot) -> u16 {
+    [
+        (input.move_up, MOVE_UP),
+        (input.move_down, MOVE_DOWN),
+        (input.move_left, MOVE_LEFT),
+        (input.move_right, MOVE_RIGHT),
+        (input.aim_up, AIM_UP),
+        (input.aim_down, AIM_DOWN),
+        (input.aim_left, AIM_LEFT),
+        (input.aim_right, AIM_RIGHT),
+        (input.shoot, SHOOT),
+        (input.melee, MELEE),
+        (input.reload, RELOAD),
+        (input.next_weapon, NEXT_WEAPON),
+        (input.previous_weapon, PREVIOUS_WEAPON),
+        (input.sticks.is_some(), STICKS),
+    ]
+    .iter()
+    .filter(|(set, _)| *set)
+    .fold(0, |flags, (_, flag)| flags | flag)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ReplayError> {
+        let end = self.position + count;
+        let taken = self.bytes.get(self.position..end)
+            .ok_or_else(|| ReplayError::Invalid(String::from("file ends early")))?;
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use quicksilver::geom::{Vector, Rectangle, Shape, Circle};
+
+// Uniform grid for broad phase collision between entities.
+// Rebuild it every tick: clear, insert everything, then query.
+// Ids are whatever the caller uses to find the entity again, usually a Vec index.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    entries: Vec<(usize, Rectangle)>,
+}
+
+impl SpatialHash {
+
+    pub fn new(cell_size: f32) -> SpatialHash {
+        SpatialHash {
+            cell_size,
+            cells: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        // keep the cell vectors around so a rebuild doesn't reallocate
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.entries.clear();
+    }
+
+    pub fn insert(&mut self, id: usize, area: Rectangle) {
+        let entry = self.entries.len();
+        self.entries.push((id, area));
+        for cell in self.cells_covering(&area) {
+            self.cells.entry(cell).or_default().push(entry);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // ids of everything overlapping the rectangle
+    pub fn query_rectangle(&self, area: &Rectangle) -> Vec<usize> {
+        self.query(area, |candidate| candidate.overlaps_rectangle(area))
+    }
+
+    // ids of everything overlapping the circle, same test as GameObject::in_range
+    pub fn query_circle(&self, circle: &Circle) -> Vec<usize> {
+        let bounds = Rectangle::new(
+            circle.pos - Vector::new(circle.radius, circle.radius),
+            Vector::new(circle.radius * 2.0, circle.radius * 2.0),
+        );
+        self.query(&bounds, |candidate| candidate.overlaps_circle(circle))
+    }
+
+    fn query(&self, bounds: &Rectangle, overlaps: impl Fn(&Rectangle) -> bool) -> Vec<usize> {
+        let mut candidates: Vec<usize> = self.cells_covering(bounds)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        // big entries sit in several cells
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        candidates.into_iter()
+            .filter(|entry| overlaps(&self.entries[*entry].1))
+            .map(|entry| self.entries[entry].0)
+            .collect()
+    }
+
+    fn cells_covering(&self, area: &Rectangle) -> impl Iterator<Item = (i32, i32)> {
+        let first_x = (area.pos.x / self.cell_size).floor() as i32;
+        let first_y = (area.pos.y / self.cell_size).floor() as i32;
+        let last_x = ((area.pos.x + area.size.x) / self.cell_size).floor() as i32;
+        let last_y = ((area.pos.y + area.size.y) / self.cell_size).floor() as i32;
+        (first_y..=last_y).flat_map(move |y| (first_x..=last_x).map(move |x| (x, y)))
+    }
+
+}
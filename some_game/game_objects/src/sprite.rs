@@ -0,0 +1,58 @@
+use serde::{Serialize, Deserialize};
+
+use crate::game_object::Direction;
+
+// Handle for an image. Game objects only carry these, the renderer keeps the
+// actual images in its asset registry and looks them up when drawing.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum SpriteId {
+    ArrowUp,
+    ArrowLeft,
+    ArrowDown,
+    ArrowRight,
+    Circle,
+    Death,
+    Wall,
+    Floor,
+}
+
+impl SpriteId {
+
+    pub const ALL: [SpriteId; 8] = [
+        SpriteId::ArrowUp,
+        SpriteId::ArrowLeft,
+        SpriteId::ArrowDown,
+        SpriteId::ArrowRight,
+        SpriteId::Circle,
+        SpriteId::Death,
+        SpriteId::Wall,
+        SpriteId::Floor,
+    ];
+
+}
+
+// one sprite per facing direction
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DirectionalSprites {
+    pub up: SpriteId,
+    pub left: SpriteId,
+    pub down: SpriteId,
+    pub right: SpriteId,
+}
+
+impl DirectionalSprites {
+
+    pub fn all(sprite: SpriteId) -> DirectionalSprites {
+        DirectionalSprites { up: sprite, left: sprite, down: sprite, right: sprite }
+    }
+
+    pub fn facing(&self, direction: Direction) -> SpriteId {
+        match direction {
+            Direction::Up => self.up,
+            Direction::Left => self.left,
+            Direction::Down => self.down,
+            Direction::Right => self.right,
+        }
+    }
+
+}
@@ -0,0 +1,96 @@
+use quicksilver::geom::{Vector, Rectangle};
+
+use crate::game_map::GameMap;
+use crate::map_layout::MapLayout;
+
+// Steering forces. Each returns a change of velocity wanted this tick; callers
+// add them up on a GameObject with add_steering() and apply them with steer(),
+// which caps the total at the object's speed.
+
+// full speed straight at the target
+pub fn seek(position: Vector, velocity: Vector, target: Vector, max_speed: f32) -> Vector {
+    let offset = target - position;
+    if offset.len() == 0.0 {
+        return -velocity;
+    }
+    offset.normalize() * max_speed - velocity
+}
+
+// full speed straight away from the target
+pub fn flee(position: Vector, velocity: Vector, target: Vector, max_speed: f32) -> Vector {
+    let offset = position - target;
+    if offset.len() == 0.0 {
+        return Vector::new(0.0, 0.0);
+    }
+    offset.normalize() * max_speed - velocity
+}
+
+// like seek, but slowing down inside slowing_radius to stop on the target
+pub fn arrive(position: Vector, velocity: Vector, target: Vector, max_speed: f32, slowing_radius: f32) -> Vector {
+    let offset = target - position;
+    let distance = offset.len();
+    if distance == 0.0 {
+        return -velocity;
+    }
+    let speed = if distance < slowing_radius { max_speed * distance / slowing_radius } else { max_speed };
+    offset.normalize() * speed - velocity
+}
+
+// Pushes away from every neighbour closer than radius, harder the closer it is.
+// Neighbours are (id, position) pairs; the object itself may be among them and is skipped.
+// Two objects on exactly the same spot are pushed apart sideways by id, so stacks always break up.
+pub fn separation(id: usize, position: Vector, neighbours: impl Iterator<Item = (usize, Vector)>, radius: f32) -> Vector {
+    let mut push = Vector::new(0.0, 0.0);
+    for (other_id, other_position) in neighbours {
+        if other_id == id {
+            continue;
+        }
+        let offset = position - other_position;
+        let distance = offset.len();
+        if distance >= radius {
+            continue;
+        }
+        let away = if distance > 0.0 {
+            offset.normalize()
+        }
+        else if id < other_id {
+            Vector::new(-1.0, 0.0)
+        }
+        else {
+            Vector::new(1.0, 0.0)
+        };
+        push += away * (1.0 - distance / radius);
+    }
+    push
+}
+
+// Looks ahead along the velocity and, if that would run into walls, pushes
+// away from them. Zero when the way ahead is clear.
+pub fn avoid_walls(game_map: &GameMap, area: &Rectangle, velocity: Vector, look_ahead_ticks: f32) -> Vector {
+    let ahead = Rectangle::new(area.pos + velocity * look_ahead_ticks, area.size);
+    let layout = game_map.layout();
+    let center = area.pos + area.size / 2.0;
+
+    let mut push = Vector::new(0.0, 0.0);
+    for (x, y) in layout.cells_overlapping(&ahead) {
+        if layout.is_walkable(x, y) {
+            continue;
+        }
+        let offset = center - MapLayout::cell_center((x, y));
+        if offset.len() > 0.0 {
+            push += offset.normalize();
+        }
+    }
+    if push.len() > 0.0 {
+        return push.normalize();
+    }
+    push
+}
+
+// shortens v to at most max_length
+pub fn truncate(v: Vector, max_length: f32) -> Vector {
+    if v.len() > max_length {
+        return v.normalize() * max_length;
+    }
+    v
+}
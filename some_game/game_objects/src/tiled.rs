@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use quicksilver::geom::{Vector, Rectangle};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::GameObjectType;
+use crate::map_layout::{MapLayout, MapError, Tile, Trigger, TILE_SIZE};
+
+// Tiled stores flip flags in the top bits of every gid
+const GID_FLAGS: u32 = 0xF000_0000;
+
+// object types/classes understood on object layers
+const PLAYER_SPAWN: &str = "player_spawn";
+const ENEMY_SPAWN: &str = "enemy_spawn";
+const TRIGGER: &str = "trigger";
+const PICKUP: &str = "pickup";
+
+// Imports maps made in the Tiled editor (https://www.mapeditor.org).
+//
+// Tiles get their GameObjectType from their class/type ("wall", "floor", ...)
+// or a "type" property, and an optional bool "collidable" property.
+// Objects typed player_spawn, enemy_spawn or trigger become spawn cells and triggers,
+// objects typed pickup place the item named like "health 2" or "weapon shotgun".
+// Tiled's tile size is scaled onto the game's 32px grid.
+pub fn load(path: impl AsRef<Path>) -> Result<MapLayout, MapError> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") | Some("tmj") => from_json(&contents),
+        _ => from_tmx(&contents),
+    }
+}
+
+pub fn from_tmx(xml: &str) -> Result<MapLayout, MapError> {
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|err| MapError::Tiled(err.to_string()))?;
+    let map_node = document.root_element();
+    if !map_node.has_tag_name("map") {
+        return Err(MapError::Tiled(String::from("root element is not <map>")));
+    }
+
+    let mut map = TiledMap {
+        width: attribute(&map_node, "width")?,
+        height: attribute(&map_node, "height")?,
+        tile_width: attribute(&map_node, "tilewidth")?,
+        tile_height: attribute(&map_node, "tileheight")?,
+        tiles: HashMap::new(),
+        layers: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for node in map_node.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "tileset" => {
+                if node.has_attribute("source") {
+                    return Err(MapError::Tiled(String::from("external tilesets are not supported")));
+                }
+                let first_gid: u32 = attribute(&node, "firstgid")?;
+                for tile_node in node.children().filter(|child| child.has_tag_name("tile")) {
+                    let id: u32 = attribute(&tile_node, "id")?;
+                    let mut info = TileInfo {
+                        kind: tile_node.attribute("class").or(tile_node.attribute("type")).map(String::from),
+                        collidable: None,
+                    };
+                    for property in tmx_properties(&tile_node) {
+                        info.apply_property(property.0, &Value::String(property.1.to_string()));
+                    }
+                    map.tiles.insert(first_gid + id, info);
+                }
+            },
+            "layer" => {
+                let data = node.children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or_else(|| MapError::Tiled(String::from("tile layer without <data>")))?;
+                if data.attribute("encoding") != Some("csv") {
+                    return Err(MapError::Tiled(String::from("only csv encoded tile layers are supported")));
+                }
+                let gids = data.text().unwrap_or("")
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|gid| !gid.is_empty())
+                    .map(|gid| gid.parse::<u32>().map_err(|err| MapError::Tiled(err.to_string())))
+                    .collect::<Result<Vec<u32>, MapError>>()?;
+                map.layers.push(gids);
+            },
+            "objectgroup" => {
+                for object_node in node.children().filter(|child| child.has_tag_name("object")) {
+                    map.objects.push(TiledObject {
+                        name: object_node.attribute("name").unwrap_or("").to_string(),
+                        kind: object_node.attribute("class").or(object_node.attribute("type")).unwrap_or("").to_string(),
+                        x: attribute(&object_node, "x")?,
+                        y: attribute(&object_node, "y")?,
+                        width: optional_attribute(&object_node, "width")?,
+                        height: optional_attribute(&object_node, "height")?,
+                    });
+                }
+            },
+            _ => (),
+        }
+    }
+
+    map.into_layout()
+}
+
+pub fn from_json(json: &str) -> Result<MapLayout, MapError> {
+    let document: JsonMap = serde_json::from_str(json)
+        .map_err(|err| MapError::Tiled(err.to_string()))?;
+
+    let mut map = TiledMap {
+        width: document.width,
+        height: document.height,
+        tile_width: document.tilewidth,
+        tile_height: document.tileheight,
+        tiles: HashMap::new(),
+        layers: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for tileset in document.tilesets {
+        if tileset.source.is_some() {
+            return Err(MapError::Tiled(String::from("external tilesets are not supported")));
+        }
+        for tile in tileset.tiles {
+            let mut info = TileInfo {
+                kind: tile.class.or(tile.kind),
+                collidable: None,
+            };
+            for property in tile.properties {
+                info.apply_property(&property.name, &property.value);
+            }
+            map.tiles.insert(tileset.firstgid + tile.id, info);
+        }
+    }
+
+    for layer in document.layers {
+        match layer.kind.as_str() {
+            "tilelayer" => {
+                let data = layer.data
+                    .ok_or_else(|| MapError::Tiled(String::from("only csv/array tile layers are supported")))?;
+                map.layers.push(data);
+            },
+            "objectgroup" => {
+                for object in layer.objects {
+                    map.objects.push(TiledObject {
+                        name: object.name,
+                        kind: object.class.or(object.kind).unwrap_or_default(),
+                        x: object.x,
+                        y: object.y,
+                        width: object.width,
+                        height: object.height,
+                    });
+                }
+            },
+            _ => (),
+        }
+    }
+
+    map.into_layout()
+}
+
+struct TileInfo {
+    kind: Option<String>,
+    collidable: Option<bool>,
+}
+
+impl TileInfo {
+
+    fn apply_property(&mut self, name: &str, value: &Value) {
+        match name {
+            "type" | "kind" => self.kind = value.as_str().map(String::from),
+            "collidable" => {
+                self.collidable = value.as_bool().or_else(|| value.as_str().map(|text| text == "true"));
+            },
+            _ => (),
+        }
+    }
+
+    fn object_type(&self) -> Option<GameObjectType> {
+        match self.kind.as_deref()?.to_ascii_lowercase().as_str() {
+            "wall" => Some(GameObjectType::Wall),
+            "floor" => Some(GameObjectType::Floor),
+            "enemy" => Some(GameObjectType::Enemy),
+            "player" => Some(GameObjectType::Player),
+            "weapon" => Some(GameObjectType::Weapon),
+            _ => None,
+        }
+    }
+
+}
+
+struct TiledObject {
+    name: String,
+    kind: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+// format independent view of a Tiled map
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tile_width: f32,
+    tile_height: f32,
+    tiles: HashMap<u32, TileInfo>,
+    layers: Vec<Vec<u32>>,
+    objects: Vec<TiledObject>,
+}
+
+impl TiledMap {
+
+    fn into_layout(self) -> Result<MapLayout, MapError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(MapError::Empty);
+        }
+        if self.tile_width <= 0.0 || self.tile_height <= 0.0 {
+            return Err(MapError::Tiled(String::from("tile size must be positive")));
+        }
+
+        let mut layout = MapLayout::new(self.width, self.height, Tile::floor());
+
+        // later layers are drawn on top, so they win
+        for gids in self.layers.iter() {
+            if gids.len() != self.width * self.height {
+                return Err(MapError::Tiled(format!(
+                    "tile layer has {} tiles, expected {}", gids.len(), self.width * self.height
+                )));
+            }
+            for (index, gid) in gids.iter().enumerate() {
+                let gid = gid & !GID_FLAGS;
+                if gid == 0 {
+                    continue;
+                }
+                let cell = (index % self.width, index / self.width);
+                let info = self.tiles.get(&gid);
+                let kind = info.and_then(TileInfo::object_type).unwrap_or(GameObjectType::Floor);
+
+                let tile = match kind {
+                    GameObjectType::Wall => Tile::wall(),
+                    GameObjectType::Player => {
+                        if layout.player_spawn().is_some_and(|spawn| spawn != cell) {
+                            return Err(MapError::Tiled(format!("second player spawn tile at {:?}", cell)));
+                        }
+                        layout.set_player_spawn(cell);
+                        Tile::floor()
+                    },
+                    GameObjectType::Enemy => {
+                        layout.add_enemy_spawn(cell);
+                        Tile::floor()
+                    },
+                    _ => Tile::floor(),
+                };
+                let collidable = info.and_then(|info| info.collidable).unwrap_or(tile.collidable);
+                layout.set_tile(cell.0, cell.1, Tile { collidable, ..tile });
+            }
+        }
+
+        let scale = Vector::new(TILE_SIZE / self.tile_width, TILE_SIZE / self.tile_height);
+        for object in self.objects.iter() {
+            let cell = match object.kind.as_str() {
+                PLAYER_SPAWN | ENEMY_SPAWN | PICKUP => self.object_cell(object, &layout)?,
+                _ => (0, 0),
+            };
+            match object.kind.as_str() {
+                PLAYER_SPAWN => {
+                    // held to the text loader's rule, one player spawn per map
+                    if layout.player_spawn().is_some() {
+                        return Err(MapError::Tiled(format!("second player spawn \"{}\"", object.name)));
+                    }
+                    layout.set_player_spawn(cell);
+                },
+                ENEMY_SPAWN => layout.add_enemy_spawn(cell),
+                TRIGGER => layout.add_trigger(Trigger {
+                    name: object.name.clone(),
+                    area: Rectangle::new(
+                        Vector::new(object.x * scale.x, object.y * scale.y),
+                        Vector::new(object.width * scale.x, object.height * scale.y),
+                    ),
+                }),
+                PICKUP => layout.add_pickup(cell, object.name.parse().map_err(MapError::Tiled)?),
+                _ => (),
+            }
+        }
+
+        Ok(layout)
+    }
+
+    // the cell a spawn or pickup object stands on, which has to be open floor on the map
+    fn object_cell(&self, object: &TiledObject, layout: &MapLayout) -> Result<(usize, usize), MapError> {
+        let (x, y) = ((object.x / self.tile_width).floor(), (object.y / self.tile_height).floor());
+        if !(x >= 0.0 && y >= 0.0 && x < self.width as f32 && y < self.height as f32) {
+            return Err(MapError::Tiled(format!("{} \"{}\" is outside the map", object.kind, object.name)));
+        }
+        let cell = (x as usize, y as usize);
+        if !layout.is_walkable(cell.0, cell.1) {
+            return Err(MapError::Tiled(format!("{} \"{}\" is on a collidable tile", object.kind, object.name)));
+        }
+        Ok(cell)
+    }
+
+}
+
+fn attribute<T: std::str::FromStr>(node: &roxmltree::Node, name: &str) -> Result<T, MapError> {
+    node.attribute(name)
+        .ok_or_else(|| MapError::Tiled(format!("<{}> is missing \"{}\"", node.tag_name().name(), name)))?
+        .parse()
+        .map_err(|_| MapError::Tiled(format!("<{}> has an invalid \"{}\"", node.tag_name().name(), name)))
+}
+
+fn optional_attribute(node: &roxmltree::Node, name: &str) -> Result<f32, MapError> {
+    if node.has_attribute(name) {
+        return attribute(node, name);
+    }
+    Ok(0.0)
+}
+
+fn tmx_properties<'a>(node: &roxmltree::Node<'a, 'a>) -> Vec<(&'a str, &'a str)> {
+    node.children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|properties| properties.children())
+        .filter(|property| property.has_tag_name("property"))
+        .filter_map(|property| Some((property.attribute("name")?, property.attribute("value")?)))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct JsonMap {
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    firstgid: u32,
+    source: Option<String>,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    class: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    data: Option<Vec<u32>>,
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    name: String,
+    class: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+}
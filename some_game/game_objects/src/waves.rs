@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+
+use crate::archetype::Archetypes;
+use crate::cooldown::{Cooldown, FRAMES_PER_SECOND};
+use crate::events::GameEvent;
+use crate::game_object::GameObject;
+
+// One wave: what it spawns and how fast. The enemies come in a random order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Wave {
+    // seconds between two spawns
+    pub interval: f32,
+    // how many of each archetype, by name
+    pub enemies: BTreeMap<String, u32>,
+    // extra enemies picked from the archetypes by spawn weight
+    pub random: u32,
+}
+
+impl Default for Wave {
+    fn default() -> Wave {
+        Wave {
+            interval: 1.0,
+            enemies: BTreeMap::new(),
+            random: 0,
+        }
+    }
+}
+
+// How much harder every wave gets than the one before it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Escalation {
+    // share of extra enemies, 0.25 gives the third wave half again as many
+    pub count: f32,
+    // extra health, rounded down
+    pub health: f32,
+    // share of extra max speed
+    pub speed: f32,
+}
+
+// The waves, read from a TOML file. They are played in order and start over
+// after the last one, still getting harder. Only the waves themselves are required:
+//
+//   spawn_distance = 192.0   # never spawn closer than this to the player
+//   break_seconds = 4.0      # before the first wave and after each cleared one
+//
+//   [escalation]
+//   count = 0.2
+//   health = 0.5
+//   speed = 0.05
+//
+//   [[wave]]
+//   interval = 1.0
+//   enemies = { chaser = 4, swarmer = 6 }
+//   random = 2
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Waves {
+    #[serde(default = "default_spawn_distance")]
+    pub spawn_distance: f32,
+    #[serde(default = "default_break_seconds")]
+    pub break_seconds: f32,
+    #[serde(default)]
+    pub escalation: Escalation,
+    #[serde(rename = "wave")]
+    pub waves: Vec<Wave>,
+}
+
+impl Waves {
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Waves, WaveError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    // every enemy named in a wave has to be one of the archetypes
+    pub fn check(&self, archetypes: &Archetypes) -> Result<(), WaveError> {
+        for wave in self.waves.iter() {
+            if let Some(name) = wave.enemies.keys().find(|name| archetypes.get(name).is_none()) {
+                return Err(WaveError::UnknownEnemy(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+}
+
+// a few random enemies at a time, more every wave
+impl Default for Waves {
+    fn default() -> Waves {
+        Waves {
+            spawn_distance: default_spawn_distance(),
+            break_seconds: default_break_seconds(),
+            escalation: Escalation { count: 0.2, health: 0.2, speed: 0.0 },
+            waves: vec![Wave { interval: 3.0, random: 5, ..Wave::default() }],
+        }
+    }
+}
+
+impl FromStr for Waves {
+    type Err = WaveError;
+
+    fn from_str(text: &str) -> Result<Waves, WaveError> {
+        let waves: Waves = toml::from_str(text)
+            .map_err(|err| WaveError::Parse(err.to_string()))?;
+        if waves.waves.is_empty() {
+            return Err(WaveError::Empty);
+        }
+        Ok(waves)
+    }
+}
+
+#[derive(Debug)]
+pub enum WaveError {
+    Io(io::Error),
+    Parse(String),
+    Empty,
+    UnknownEnemy(String),
+}
+
+impl fmt::Display for WaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaveError::Io(err) => write!(f, "could not read waves: {}", err),
+            WaveError::Parse(message) => write!(f, "invalid waves: {}", message),
+            WaveError::Empty => write!(f, "no waves defined"),
+            WaveError::UnknownEnemy(name) => write!(f, "a wave spawns \"{}\", which is no enemy archetype", name),
+        }
+    }
+}
+
+impl std::error::Error for WaveError {}
+
+impl From<io::Error> for WaveError {
+    fn from(err: io::Error) -> WaveError {
+        WaveError::Io(err)
+    }
+}
+
+// Runs the waves: a break, then the next wave's enemies one at a time and,
+// once every enemy is dead, another break. It only decides what spawns and
+// when, the world finds somewhere to put it.
+#[derive(Clone, Debug)]
+pub struct WaveDirector {
+    waves: Waves,
+    // waves started so far, so also the number of the running one
+    wave: u32,
+    // what the running wave has yet to spawn, the next one last
+    queue: Vec<String>,
+    spawn_timer: Cooldown,
+    // ticks left of the break, 0 while a wave runs
+    break_frames: u32,
+}
+
+impl WaveDirector {
+
+    pub fn new(waves: Waves) -> WaveDirector {
+        let break_frames = seconds_to_frames(waves.break_seconds).max(1);
+        WaveDirector {
+            waves,
+            wave: 0,
+            queue: Vec::new(),
+            spawn_timer: Cooldown::with_frames(0),
+            break_frames,
+        }
+    }
+
+    // Call once per tick with the number of wave spawned enemies alive.
+    // Returns the archetype to spawn this tick, if any.
+    pub fn tick(&mut self, archetypes: &Archetypes, enemies_alive: usize, rng: &mut StdRng, events: &mut Vec<GameEvent>) -> Option<String> {
+        if self.break_frames > 0 {
+            self.break_frames -= 1;
+            if self.break_frames == 0 {
+                self.start_wave(archetypes, rng);
+                events.push(GameEvent::WaveStarted { wave: self.wave });
+            }
+            return None;
+        }
+
+        if !self.queue.is_empty() {
+            self.spawn_timer.tick();
+            if !self.spawn_timer.is_ready() {
+                return None;
+            }
+            self.spawn_timer.reset();
+            return self.queue.pop();
+        }
+
+        if enemies_alive == 0 {
+            events.push(GameEvent::WaveCleared { wave: self.wave });
+            self.break_frames = seconds_to_frames(self.waves.break_seconds).max(1);
+        }
+        None
+    }
+
+    fn start_wave(&mut self, archetypes: &Archetypes, rng: &mut StdRng) {
+        let scale = 1.0 + self.waves.escalation.count * self.wave as f32;
+        let scaled = |count: u32| (count as f32 * scale).round() as u32;
+        let wave = &self.waves.waves[self.wave as usize % self.waves.waves.len()];
+
+        self.queue.clear();
+        for (name, count) in wave.enemies.iter() {
+            for _ in 0..scaled(*count) {
+                self.queue.push(name.clone());
+            }
+        }
+        for _ in 0..scaled(wave.random) {
+            if let Some(name) = archetypes.choose(rng) {
+                self.queue.push(name.to_string());
+            }
+        }
+        self.queue.shuffle(rng);
+        self.spawn_timer = Cooldown::with_frames(seconds_to_frames(wave.interval));
+        self.wave += 1;
+    }
+
+    // for a spawn that found nowhere to go, it comes next again
+    pub fn put_back(&mut self, name: String) {
+        self.queue.push(name);
+    }
+
+    // Makes a freshly spawned enemy as much tougher as the running wave is
+    // harder than the first.
+    pub fn escalate(&self, enemy: &mut GameObject) {
+        let level = self.wave.saturating_sub(1) as f32;
+        let escalation = &self.waves.escalation;
+        enemy.set_max_health(enemy.max_health() + (escalation.health * level) as i32);
+        enemy.set_max_speed(enemy.max_speed() * (1.0 + escalation.speed * level));
+    }
+
+    // the running wave, or the last one during a break, 0 before the first
+    pub fn wave(&self) -> u32 {
+        self.wave
+    }
+
+    pub fn is_on_break(&self) -> bool {
+        self.break_frames > 0
+    }
+
+    pub fn break_frames_left(&self) -> u32 {
+        self.break_frames
+    }
+
+    pub fn enemies_to_spawn(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn spawn_distance(&self) -> f32 {
+        self.waves.spawn_distance
+    }
+
+}
+
+fn default_spawn_distance() -> f32 {
+    160.0
+}
+
+fn default_break_seconds() -> f32 {
+    3.0
+}
+
+fn seconds_to_frames(seconds: f32) -> u32 {
+    (seconds * FRAMES_PER_SECOND as f32).round() as u32
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+
+use crate::cooldown::FRAMES_PER_SECOND;
+use crate::sprite::SpriteId;
+
+// Guns, read from a TOML file with one table per weapon. Every field is optional,
+// anything left out is what the pistol has:
+//
+//   [shotgun]
+//   slot = 2               # order when switching weapons, lowest first
+//   shots_per_second = 1.0 # above 0
+//   projectiles = 6        # bullets per shot
+//   spread = 40.0          # degrees the bullets of one shot fan out over
+//   speed = 7.0            # pixels per tick
+//   damage = 1
+//   range = 200.0
+//   size = [8.0, 8.0]
+//   sprite = "Circle"
+//   ricochets = 0          # walls a bullet bounces off before it is spent
+//   pierce = 0             # enemies a bullet goes through before it is spent
+//   magazine = 8           # shots between reloads, 0 never reloads
+//   ammo = 32              # spare rounds carried at the start, leave out for endless
+//   reload_seconds = 1.5
+//   locked = false         # true to start without it, until a weapon pickup unlocks it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Weapon {
+    pub slot: u32,
+    pub shots_per_second: f32,
+    pub projectiles: u32,
+    pub spread: f32,
+    pub speed: f32,
+    pub damage: i32,
+    pub range: f32,
+    pub size: [f32; 2],
+    pub sprite: SpriteId,
+    pub ricochets: u32,
+    pub pierce: u32,
+    pub magazine: u32,
+    pub ammo: Option<u32>,
+    pub reload_seconds: f32,
+    pub locked: bool,
+}
+
+// the pistol, what every gun used to be
+impl Default for Weapon {
+    fn default() -> Weapon {
+        Weapon {
+            slot: 0,
+            shots_per_second: 2.0,
+            projectiles: 1,
+            spread: 0.0,
+            speed: 8.0,
+            damage: 1,
+            range: 300.0,
+            size: [12.0, 12.0],
+            sprite: SpriteId::Circle,
+            ricochets: 0,
+            pierce: 0,
+            magazine: 0,
+            ammo: None,
+            reload_seconds: 1.0,
+            locked: false,
+        }
+    }
+}
+
+// The rounds a weapon has left and its reload. A shot uses one round however
+// many projectiles it fires.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ammo {
+    // rounds in the magazine
+    loaded: u32,
+    magazine: u32,
+    // None for endless
+    spare: Option<u32>,
+    reload_frames: u32,
+    // ticks until the running reload is done, 0 when not reloading
+    reloading: u32,
+}
+
+impl Ammo {
+
+    // a full magazine and all the spare rounds the weapon starts with
+    pub fn full(weapon: &Weapon) -> Ammo {
+        Ammo {
+            loaded: weapon.magazine,
+            magazine: weapon.magazine,
+            spare: weapon.ammo,
+            reload_frames: (weapon.reload_seconds * FRAMES_PER_SECOND as f32).round() as u32,
+            reloading: 0,
+        }
+    }
+
+    pub fn loaded(&self) -> u32 {
+        self.loaded
+    }
+
+    pub fn magazine(&self) -> u32 {
+        self.magazine
+    }
+
+    pub fn spare(&self) -> Option<u32> {
+        self.spare
+    }
+
+    // without a magazine a weapon never runs out
+    pub fn is_empty(&self) -> bool {
+        self.magazine > 0 && self.loaded == 0
+    }
+
+    // empty, and nothing left to reload with
+    pub fn is_out(&self) -> bool {
+        self.is_empty() && self.spare == Some(0)
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reloading > 0
+    }
+
+    pub fn reload_frames_left(&self) -> u32 {
+        self.reloading
+    }
+
+    pub fn can_fire(&self) -> bool {
+        !self.is_empty() && !self.is_reloading()
+    }
+
+    pub fn use_round(&mut self) {
+        if self.magazine > 0 {
+            self.loaded = self.loaded.saturating_sub(1);
+        }
+    }
+
+    // Starts reloading. Returns false when there is nothing to do: no magazine,
+    // already full or reloading, or no spare rounds.
+    pub fn reload(&mut self) -> bool {
+        if self.magazine == 0 || self.loaded >= self.magazine || self.is_reloading() || self.spare == Some(0) {
+            return false;
+        }
+        self.reloading = self.reload_frames.max(1);
+        true
+    }
+
+    pub fn cancel_reload(&mut self) {
+        self.reloading = 0;
+    }
+
+    // Advances a running reload, call once per tick.
+    // Returns true on the tick it finishes and the magazine is refilled.
+    pub fn tick(&mut self) -> bool {
+        if self.reloading == 0 {
+            return false;
+        }
+        self.reloading -= 1;
+        if self.reloading > 0 {
+            return false;
+        }
+        let wanted = self.magazine - self.loaded;
+        let taken = self.spare.map_or(wanted, |spare| spare.min(wanted));
+        self.loaded += taken;
+        self.spare = self.spare.map(|spare| spare - taken);
+        true
+    }
+
+    // endless stays endless
+    pub fn add_spare(&mut self, rounds: u32) {
+        self.spare = self.spare.map(|spare| spare.saturating_add(rounds));
+    }
+
+}
+
+// no magazine, never runs out
+impl Default for Ammo {
+    fn default() -> Ammo {
+        Ammo::full(&Weapon::default())
+    }
+}
+
+#[derive(Debug)]
+pub enum WeaponError {
+    Io(io::Error),
+    Parse(String),
+    Empty,
+}
+
+impl fmt::Display for WeaponError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeaponError::Io(err) => write!(f, "could not read weapons: {}", err),
+            WeaponError::Parse(message) => write!(f, "invalid weapons: {}", message),
+            WeaponError::Empty => write!(f, "no weapons defined"),
+        }
+    }
+}
+
+impl std::error::Error for WeaponError {}
+
+impl From<io::Error> for WeaponError {
+    fn from(err: io::Error) -> WeaponError {
+        WeaponError::Io(err)
+    }
+}
+
+// Every weapon by name, never empty.
+#[derive(Clone, Debug)]
+pub struct Weapons {
+    weapons: BTreeMap<String, Weapon>,
+}
+
+impl Weapons {
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Weapons, WeaponError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Weapon> {
+        self.weapons.get(name)
+    }
+
+    // in switching order: by slot, then by name
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.weapons.keys().map(String::as_str).collect();
+        names.sort_by_key(|name| self.weapons[*name].slot);
+        names
+    }
+
+    pub fn insert(&mut self, name: &str, weapon: Weapon) {
+        self.weapons.insert(name.to_string(), weapon);
+    }
+
+    pub fn len(&self) -> usize {
+        self.weapons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weapons.is_empty()
+    }
+
+}
+
+// just the pistol
+impl Default for Weapons {
+    fn default() -> Weapons {
+        let mut weapons = Weapons { weapons: BTreeMap::new() };
+        weapons.insert("pistol", Weapon::default());
+        weapons
+    }
+}
+
+impl FromStr for Weapons {
+    type Err = WeaponError;
+
+    fn from_str(text: &str) -> Result<Weapons, WeaponError> {
+        let weapons: BTreeMap<String, Weapon> = toml::from_str(text)
+            .map_err(|err| WeaponError::Parse(err.to_string()))?;
+        if weapons.is_empty() {
+            return Err(WeaponError::Empty);
+        }
+        // at 0 a weapon would never fire, below 0 it would fire every tick
+        for (name, weapon) in weapons.iter() {
+            if !(weapon.shots_per_second > 0.0 && weapon.shots_per_second.is_finite()) {
+                return Err(WeaponError::Parse(format!("{} needs a shots_per_second above 0", name)));
+            }
+        }
+        Ok(Weapons { weapons })
+    }
+}
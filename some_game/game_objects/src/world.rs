@@ -0,0 +1,593 @@
+use std::mem;
+
+use quicksilver::geom::{Vector, Circle, Shape};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::ai::AiState;
+use crate::archetype::Archetypes;
+use crate::events::GameEvent;
+use crate::flow_field::FlowField;
+use crate::game_map::GameMap;
+use crate::game_object::{GameObject, Direction, Behavior};
+use crate::map_layout::MapLayout;
+use crate::pickup::{Pickup, Item};
+use crate::spatial_hash::SpatialHash;
+use crate::steering;
+use crate::waves::{Waves, WaveDirector};
+use crate::weapon::{Weapons, Ammo};
+
+// enemies closer than this many of their own widths push each other apart
+const SEPARATION_SPACING: f32 = 1.5;
+const SEPARATION_WEIGHT: f32 = 1.5;
+// ticks of movement enemies look ahead for walls
+const WALL_LOOK_AHEAD: f32 = 8.0;
+const WALL_AVOIDANCE_WEIGHT: f32 = 1.0;
+// pixels per tick a magnetised pickup flies towards the player
+const MAGNET_SPEED: f32 = 5.0;
+
+// Everything the player asked for during one tick, captured by whoever owns the
+// window (or a test) so the simulation never touches an input device.
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct InputSnapshot {
+    pub move_up: bool,
+    pub move_down: bool,
+    pub move_left: bool,
+    pub move_right: bool,
+    pub aim_up: bool,
+    pub aim_down: bool,
+    pub aim_left: bool,
+    pub aim_right: bool,
+    pub shoot: bool,
+    pub melee: bool,
+    pub reload: bool,
+    // switch weapons, only set on the tick the button went down
+    pub next_weapon: bool,
+    pub previous_weapon: bool,
+    // (left, right) stick positions, only set on ticks where a stick moved
+    pub sticks: Option<(Vector, Vector)>,
+}
+
+// The whole game simulation: map, player, enemies and bullets.
+// It knows nothing about drawing, call tick() and then render what it holds.
+// All randomness comes from the world's own rng, so the same seed and
+// the same inputs always play out the same way.
+pub struct World {
+    map: GameMap,
+    player: GameObject,
+    enemies: Vec<GameObject>,
+    bullets: Vec<GameObject>,
+    enemy_bullets: Vec<GameObject>,
+    pickups: Vec<Pickup>,
+    // pickups closer than this to the player fly to them, 0 for none
+    magnet_radius: f32,
+    score: u32,
+    archetypes: Archetypes,
+    weapons: Weapons,
+    // index into weapons.names() of what the player holds
+    player_weapon: usize,
+    // what each of them has left, in the same order
+    ammo: Vec<Ammo>,
+    // which of them the player has, locked ones only after picking them up
+    owned: Vec<bool>,
+    director: WaveDirector,
+    enemy_grid: SpatialHash,
+    // leads every chasing enemy to the player's tile
+    flow_field: FlowField,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl World {
+
+    // The player is placed on the map's player spawn, an enemy, picked from
+    // the archetypes by spawn weight, on every enemy spawn and the map's
+    // pickups where they lie.
+    pub fn new(map: GameMap, mut player: GameObject, archetypes: Archetypes, seed: u64) -> World {
+
+        if let Some(spawn) = map.player_spawn() {
+            player.set_position(spawn);
+            player.remember_position();
+        }
+
+        let enemy_spawns = map.enemy_spawns();
+        let pickups = map.pickups().into_iter()
+            .map(|(center, item)| Pickup::new(center, item))
+            .collect();
+        let mut world = World {
+            map,
+            player,
+            enemies: Vec::new(),
+            bullets: Vec::new(),
+            enemy_bullets: Vec::new(),
+            pickups,
+            magnet_radius: 0.0,
+            score: 0,
+            archetypes,
+            weapons: Weapons::default(),
+            player_weapon: 0,
+            ammo: Vec::new(),
+            owned: Vec::new(),
+            director: WaveDirector::new(Waves::default()),
+            enemy_grid: SpatialHash::new(64.0),
+            flow_field: FlowField::new(),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        for spawn in enemy_spawns {
+            world.spawn_random_enemy(spawn);
+        }
+        world.set_weapons(Weapons::default());
+        world
+    }
+
+    // What the player can switch between, all fully loaded, starting with the
+    // first one that isn't locked (or the first of them, if all are). Call
+    // before the first tick, like everything else that shapes a replay.
+    pub fn set_weapons(&mut self, weapons: Weapons) {
+        let names = weapons.names();
+        self.ammo = names.iter()
+            .filter_map(|name| weapons.get(name))
+            .map(Ammo::full)
+            .collect();
+        self.owned = names.iter()
+            .filter_map(|name| weapons.get(name))
+            .map(|weapon| !weapon.locked)
+            .collect();
+        self.player_weapon = self.owned.iter().position(|owned| *owned).unwrap_or(0);
+        self.owned[self.player_weapon] = true;
+        self.weapons = weapons;
+        self.equip_player();
+    }
+
+    // Starts over from the break before the first wave.
+    pub fn set_waves(&mut self, waves: Waves) {
+        self.director = WaveDirector::new(waves);
+    }
+
+    // Pickups closer than radius to the player fly towards them, 0 turns it off.
+    pub fn set_magnet_radius(&mut self, radius: f32) {
+        self.magnet_radius = radius;
+    }
+
+    // Moves steps through the owned weapons, wrapping around.
+    fn switch_weapon(&mut self, steps: isize) {
+        let count = self.ammo.len() as isize;
+        let mut index = self.player_weapon as isize;
+        for _ in 0..steps.abs() {
+            // the one in hand is owned, so this always stops
+            loop {
+                index = (index + steps.signum()).rem_euclid(count);
+                if self.owned[index as usize] {
+                    break;
+                }
+            }
+        }
+        self.hold_weapon(index as usize);
+    }
+
+    // The one put away keeps its ammo, but a reload has to start over.
+    fn hold_weapon(&mut self, index: usize) {
+        let mut ammo = *self.player.ammo();
+        ammo.cancel_reload();
+        self.ammo[self.player_weapon] = ammo;
+        self.player_weapon = index;
+        self.equip_player();
+    }
+
+    fn equip_player(&mut self) {
+        let names = self.weapons.names();
+        if let Some(weapon) = self.weapons.get(names[self.player_weapon]) {
+            self.player.equip(weapon);
+            self.player.set_ammo(self.ammo[self.player_weapon]);
+        }
+    }
+
+    // false when there is no archetype with that name
+    pub fn spawn_enemy(&mut self, name: &str, position: Vector) -> bool {
+        match self.archetypes.spawn(name, position) {
+            Some(enemy) => {
+                self.enemies.push(enemy);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // A floor tile for the next wave enemy, out of the player's reach: one of
+    // the map's enemy spawns if any is far enough away, otherwise any floor tile that is.
+    fn wave_spawn(&mut self) -> Option<(usize, usize)> {
+        let layout = self.map.layout();
+        let player = self.player.center();
+        let distance = self.director.spawn_distance();
+        let usable = |cell: &(usize, usize)| {
+            layout.is_walkable(cell.0, cell.1) && (MapLayout::cell_center(*cell) - player).len() >= distance
+        };
+
+        let spawns: Vec<(usize, usize)> = layout.enemy_spawns().iter().copied().filter(usable).collect();
+        if !spawns.is_empty() {
+            return spawns.choose(&mut self.rng).copied();
+        }
+        let floor: Vec<(usize, usize)> = (0..layout.height())
+            .flat_map(|y| (0..layout.width()).map(move |x| (x, y)))
+            .filter(usable)
+            .collect();
+        floor.choose(&mut self.rng).copied()
+    }
+
+    // centered on the tile, and as much tougher as the wave calls for
+    fn spawn_wave_enemy(&mut self, name: &str, cell: (usize, usize)) {
+        if let Some(mut enemy) = self.archetypes.spawn(name, MapLayout::cell_position(cell)) {
+            enemy.set_position(MapLayout::cell_center(cell) - enemy.size() / 2.0);
+            enemy.remember_position();
+            self.director.escalate(&mut enemy);
+            enemy.set_wave(Some(self.director.wave()));
+            self.enemies.push(enemy);
+        }
+    }
+
+    fn spawn_random_enemy(&mut self, position: Vector) {
+        if let Some(name) = self.archetypes.choose(&mut self.rng).map(String::from) {
+            self.spawn_enemy(&name, position);
+        }
+    }
+
+    // Advances the simulation by one fixed step of 1 / FRAMES_PER_SECOND seconds.
+    // All speeds and cooldowns are per step, so call this at a fixed rate
+    // however fast the screen refreshes.
+    pub fn tick(&mut self, input: &InputSnapshot) -> Vec<GameEvent> {
+
+        let mut events: Vec<GameEvent> = Vec::new();
+        if self.player.is_dead() {
+            return events;
+        }
+
+        self.player.remember_position();
+        for object in self.enemies.iter_mut().chain(self.bullets.iter_mut()).chain(self.enemy_bullets.iter_mut()) {
+            object.remember_position();
+        }
+        for pickup in self.pickups.iter_mut() {
+            pickup.remember_position();
+        }
+
+        self.apply_input(input, &mut events);
+
+        // cull bullets
+        self.bullets.retain(|bullet| !bullet.out_of_range());
+        self.enemy_bullets.retain(|bullet| !bullet.out_of_range());
+
+        self.player.accelerate();
+        self.player.carry_momentum(&self.map);
+        let was_reloading = self.player.ammo().is_reloading();
+        self.player.update_timers();
+        if was_reloading && !self.player.ammo().is_reloading() {
+            events.push(GameEvent::Reloaded);
+        }
+        // bullets that hit a wall without a ricochet left are gone
+        let map = &self.map;
+        for bullets in [&mut self.bullets, &mut self.enemy_bullets] {
+            bullets.retain_mut(|bullet| match bullet.carry_projectile(map) {
+                Some(event) => {
+                    events.push(event);
+                    !matches!(event, GameEvent::Impact { .. })
+                },
+                None => true,
+            });
+        }
+
+        // a bullet is used up by the first enemy it touches, hurt or not, unless
+        // it can pierce: then it goes on through as many as it damages
+        self.rebuild_enemy_grid();
+        let enemies = &mut self.enemies;
+        let enemy_grid = &self.enemy_grid;
+        self.bullets.retain_mut(|bullet| {
+            // the nearest of everything it flew through this tick
+            let mut hit: Vec<usize> = enemy_grid.query_rectangle(&bullet.swept_sprite())
+                .into_iter()
+                .filter(|index| bullet.passed_through(&enemies[*index].sprite()))
+                .collect();
+            hit.sort_by(|first, second| {
+                let distance = |index: &usize| (enemies[*index].center() - bullet.previous_position()).len();
+                distance(first).total_cmp(&distance(second))
+            });
+            for index in hit {
+                let event = enemies[index].take_damage(bullet.damage());
+                let damaged = event.is_some();
+                events.extend(event);
+                if bullet.pierce() == 0 {
+                    return false;
+                }
+                if damaged {
+                    bullet.set_pierce(bullet.pierce() - 1);
+                }
+            }
+            true
+        });
+
+        // the swinging weapon hits each enemy it touches once per swing
+        if self.player.is_swinging() {
+            for index in self.enemy_grid.query_rectangle(&self.player.weapon().sprite()) {
+                events.extend(self.player.strike(&mut self.enemies[index]));
+            }
+        }
+
+        // every entry of a dead enemy's loot table gets its own roll
+        for enemy in self.enemies.iter().filter(|enemy| enemy.is_dead()) {
+            for loot in enemy.loot() {
+                if self.rng.gen::<f32>() < loot.chance {
+                    self.pickups.push(Pickup::new(enemy.center(), loot.item.clone()));
+                }
+            }
+        }
+
+        // cull dead enemies
+        self.enemies.retain(|enemy| !enemy.is_dead());
+
+        self.collect_pickups(&mut events);
+
+        // the director says what spawns, and it waits while there is nowhere to put it.
+        // Only what the waves spawned counts towards clearing one, not the map's own enemies
+        let alive = self.enemies.iter().filter(|enemy| enemy.wave().is_some()).count();
+        if let Some(name) = self.director.tick(&self.archetypes, alive, &mut self.rng, &mut events) {
+            match self.wave_spawn() {
+                Some(cell) => self.spawn_wave_enemy(&name, cell),
+                None => self.director.put_back(name),
+            }
+        }
+
+        // contact damage
+        self.rebuild_enemy_grid();
+        for index in self.enemy_grid.query_rectangle(&self.player.sprite()) {
+            if let Some(event) = self.player.take_damage(self.enemies[index].damage()) {
+                events.push(event);
+            }
+        }
+
+        // enemy shots are used up by hitting the player, even while invulnerable
+        let player = &mut self.player;
+        self.enemy_bullets.retain(|bullet| {
+            if !bullet.passed_through(&player.sprite()) {
+                return true;
+            }
+            events.extend(player.take_damage(bullet.damage()));
+            false
+        });
+
+        // only rebuilt when the player moves to another tile
+        if let Some(player_cell) = self.map.layout().cell_at(self.player.center()) {
+            self.flow_field.update(self.map.layout(), player_cell);
+        }
+
+        // enemies keep some room between each other, from where they all stood
+        // at the start of this step
+        let separation: Vec<Vector> = self.enemies.iter()
+            .enumerate()
+            .map(|(index, enemy)| {
+                let radius = enemy.size().x.max(enemy.size().y) * SEPARATION_SPACING;
+                let neighbours = self.enemy_grid.query_circle(&Circle::new(enemy.center(), radius))
+                    .into_iter()
+                    .map(|other| (other, self.enemies[other].center()));
+                steering::separation(index, enemy.center(), neighbours, radius) * enemy.speed() * SEPARATION_WEIGHT
+            })
+            .collect();
+
+        for (enemy, separation) in self.enemies.iter_mut().zip(separation) {
+            enemy.update_timers();
+            enemy.patrol_for(&self.player, &self.map, &self.flow_field, &mut self.rng);
+            enemy.add_steering(separation);
+            let avoidance = steering::avoid_walls(&self.map, &enemy.sprite(), enemy.velocity(), WALL_LOOK_AHEAD);
+            enemy.add_steering(avoidance * enemy.speed() * WALL_AVOIDANCE_WEIGHT);
+            enemy.steer();
+            // shooters stop at half their range and fire instead of closing in
+            if enemy.behavior() == Behavior::Shoot && enemy.ai_state() == AiState::Chase {
+                let distance = (self.player.center() - enemy.center()).len();
+                if distance <= enemy.range() / 2.0 {
+                    enemy.set_velocity(Vector::new(0.0, 0.0));
+                    enemy.aim_at(self.player.center());
+                    enemy.shoot(&mut self.enemy_bullets);
+                }
+            }
+            enemy.carry_momentum(&self.map);
+        }
+
+        events
+    }
+
+    fn apply_input(&mut self, input: &InputSnapshot, events: &mut Vec<GameEvent>) {
+
+        let player = &mut self.player;
+
+        if let Some((left_stick, right_stick)) = input.sticks {
+            player.set_acceleration(left_stick);
+            player.set_direction(right_stick);
+        }
+
+        //move
+        if input.move_left {
+            player.move_left();
+        }
+        if input.move_right {
+            player.move_right();
+        }
+        if input.move_up {
+            player.move_up();
+        }
+        if input.move_down {
+            player.move_down();
+        }
+
+        // direction changes
+        if input.aim_left {
+            player.update_direction(Direction::Left);
+        }
+        if input.aim_right {
+            player.update_direction(Direction::Right);
+        }
+        if input.aim_up {
+            player.update_direction(Direction::Up);
+        }
+        if input.aim_down {
+            player.update_direction(Direction::Down);
+        }
+        if input.reload {
+            player.reload();
+        }
+        if input.shoot {
+            events.extend(player.shoot(&mut self.bullets));
+        }
+        if input.melee {
+            player.swing();
+        }
+
+        if input.next_weapon {
+            self.switch_weapon(1);
+        }
+        if input.previous_weapon {
+            self.switch_weapon(-1);
+        }
+    }
+
+    // Pulls in the pickups within the magnet radius, then uses the ones the
+    // player touches. Those the player has no use for stay where they are.
+    fn collect_pickups(&mut self, events: &mut Vec<GameEvent>) {
+        let player_center = self.player.center();
+        for pickup in self.pickups.iter_mut() {
+            if (pickup.center() - player_center).len() < self.magnet_radius {
+                pickup.pull_towards(player_center, MAGNET_SPEED);
+            }
+        }
+
+        let mut pickups = mem::take(&mut self.pickups);
+        pickups.retain(|pickup| {
+            if !pickup.object().sprite().overlaps_rectangle(&self.player.sprite()) || !self.use_item(pickup.item()) {
+                return true;
+            }
+            events.push(GameEvent::PickedUp { kind: pickup.item().kind(), position: pickup.center() });
+            false
+        });
+        self.pickups = pickups;
+    }
+
+    // false when it would do nothing
+    fn use_item(&mut self, item: &Item) -> bool {
+        match item {
+            Item::Health { amount } => {
+                if self.player.health() >= self.player.max_health() {
+                    return false;
+                }
+                self.player.heal(*amount);
+            },
+            Item::Ammo { rounds } => {
+                if self.player.ammo().spare().is_none() {
+                    return false;
+                }
+                let mut ammo = *self.player.ammo();
+                ammo.add_spare(*rounds);
+                self.player.set_ammo(ammo);
+            },
+            Item::Weapon { name } => {
+                let index = match self.weapons.names().iter().position(|weapon| weapon == name) {
+                    Some(index) => index,
+                    None => return false,
+                };
+                // a weapon already owned only brings its spare rounds
+                if self.owned[index] {
+                    let rounds = self.weapons.get(name).and_then(|weapon| weapon.ammo);
+                    match rounds {
+                        Some(rounds) if index == self.player_weapon => {
+                            let mut ammo = *self.player.ammo();
+                            ammo.add_spare(rounds);
+                            self.player.set_ammo(ammo);
+                        },
+                        Some(rounds) => self.ammo[index].add_spare(rounds),
+                        None => return false,
+                    }
+                } else {
+                    self.owned[index] = true;
+                    self.hold_weapon(index);
+                }
+            },
+            Item::Gem { score } => self.score += score,
+        }
+        true
+    }
+
+    fn rebuild_enemy_grid(&mut self) {
+        self.enemy_grid.clear();
+        for (index, enemy) in self.enemies.iter().enumerate() {
+            self.enemy_grid.insert(index, enemy.sprite());
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.player.is_dead()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn map(&self) -> &GameMap {
+        &self.map
+    }
+
+    pub fn player(&self) -> &GameObject {
+        &self.player
+    }
+
+    pub fn enemies(&self) -> &Vec<GameObject> {
+        &self.enemies
+    }
+
+    pub fn bullets(&self) -> &Vec<GameObject> {
+        &self.bullets
+    }
+
+    pub fn enemy_bullets(&self) -> &Vec<GameObject> {
+        &self.enemy_bullets
+    }
+
+    pub fn pickups(&self) -> &Vec<Pickup> {
+        &self.pickups
+    }
+
+    // what the gems picked up so far are worth
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn director(&self) -> &WaveDirector {
+        &self.director
+    }
+
+    pub fn flow_field(&self) -> &FlowField {
+        &self.flow_field
+    }
+
+    pub fn archetypes(&self) -> &Archetypes {
+        &self.archetypes
+    }
+
+    pub fn weapons(&self) -> &Weapons {
+        &self.weapons
+    }
+
+    // the name of the weapon the player holds
+    pub fn weapon(&self) -> &str {
+        self.weapons.names()[self.player_weapon]
+    }
+
+    // what the player's weapon has left
+    pub fn ammo(&self) -> &Ammo {
+        self.player.ammo()
+    }
+
+    // whether the player has the weapon, false when there is none by that name
+    pub fn owns(&self, name: &str) -> bool {
+        self.weapons.names().iter()
+            .position(|weapon| *weapon == name)
+            .is_some_and(|index| self.owned[index])
+    }
+
+}
@@ -0,0 +1,112 @@
+use quicksilver::geom::Vector;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use game_objects::ai::{Ai, AiConfig, AiState, Senses};
+use game_objects::flow_field::FlowField;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::map_layout::MapLayout;
+use game_objects::sprite::SpriteId;
+
+fn unseen() -> Senses {
+    Senses { sees_target: false, target_position: Vector::new(0.0, 0.0), distance: 1000.0, health: 1.0 }
+}
+
+fn seen_at(position: Vector) -> Senses {
+    Senses { sees_target: true, target_position: position, distance: 100.0, health: 1.0 }
+}
+
+fn config() -> AiConfig {
+    AiConfig { idle_frames: 10, wander_frames: 20, search_frames: 30, flee_health: 0.5, ..AiConfig::default() }
+}
+
+#[test]
+fn idle_turns_into_wander_after_a_while() {
+    let mut ai = Ai::new(config());
+    for _ in 0..9 {
+        assert_eq!(ai.think(&unseen()), AiState::Idle);
+    }
+    assert_eq!(ai.think(&unseen()), AiState::Wander);
+    assert!(!ai.has_route());
+}
+
+#[test]
+fn seeing_the_target_starts_a_chase_from_any_state() {
+    for warm_up in [0, 15] {
+        let mut ai = Ai::new(config());
+        for _ in 0..warm_up {
+            ai.think(&unseen());
+        }
+        assert_eq!(ai.think(&seen_at(Vector::new(5.0, 5.0))), AiState::Chase);
+        assert_eq!(ai.last_known_position(), Some(Vector::new(5.0, 5.0)));
+    }
+}
+
+#[test]
+fn losing_sight_searches_then_gives_up() {
+    let mut ai = Ai::new(config());
+    ai.think(&seen_at(Vector::new(5.0, 5.0)));
+    assert_eq!(ai.think(&unseen()), AiState::Search);
+    assert_eq!(ai.last_known_position(), Some(Vector::new(5.0, 5.0)));
+
+    for _ in 0..29 {
+        assert_eq!(ai.think(&unseen()), AiState::Search);
+    }
+    assert_eq!(ai.think(&unseen()), AiState::Idle);
+    assert_eq!(ai.last_known_position(), None);
+}
+
+#[test]
+fn searches_end_when_the_route_is_walked() {
+    let mut ai = Ai::new(config());
+    ai.think(&seen_at(Vector::new(5.0, 5.0)));
+    ai.think(&unseen());
+    ai.set_route(vec![Vector::new(5.0, 5.0)]);
+    assert_eq!(ai.next_waypoint(Vector::new(5.0, 6.0), 4.0), None);
+    assert_eq!(ai.think(&unseen()), AiState::Idle);
+}
+
+#[test]
+fn low_health_flees_until_far_enough() {
+    let mut ai = Ai::new(config());
+    let hurt = Senses { health: 0.4, ..seen_at(Vector::new(0.0, 0.0)) };
+    assert_eq!(ai.think(&hurt), AiState::Flee);
+    assert_eq!(ai.think(&Senses { distance: 500.0, ..hurt }), AiState::Idle);
+    assert_eq!(ai.think(&Senses { health: 0.6, ..hurt }), AiState::Chase);
+}
+
+#[test]
+fn wander_keeps_its_waypoint_between_ticks() {
+    let map: GameMap = "\
+##########
+#........#
+#........#
+#........#
+#........#
+##########
+".parse().unwrap();
+    let flow_field = FlowField::new();
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut enemy = GameObject::new_enemy(MapLayout::cell_center((4, 2)), SpriteId::Circle);
+    enemy.set_ai_config(AiConfig { idle_frames: 1, wander_frames: 1000, ..AiConfig::default() });
+    // far away and out of range
+    let target = GameObject::new_enemy(Vector::new(5000.0, 5000.0), SpriteId::Circle);
+
+    enemy.patrol_for(&target, &map, &flow_field, &mut rng);
+    assert_eq!(enemy.ai_state(), AiState::Wander);
+    let destination = *enemy.ai().route().unwrap().last().unwrap();
+
+    let start = enemy.center();
+    for _ in 0..30 {
+        enemy.patrol_for(&target, &map, &flow_field, &mut rng);
+        enemy.steer();
+        enemy.carry_momentum(&map);
+        if enemy.ai_state() != AiState::Wander {
+            break;
+        }
+        assert_eq!(enemy.ai().route().unwrap().last(), Some(&destination));
+    }
+    // actually heading there instead of jittering in place
+    assert!((enemy.center() - destination).len() < (start - destination).len());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use game_objects::events::GameEvent;
+use game_objects::game_map::GameMap;
+use game_objects::weapon::{Ammo, Weapon};
+use game_objects::world::{World, InputSnapshot};
+
+const WEAPONS: &str = "
+[revolver]
+slot = 1
+shots_per_second = 10.0
+magazine = 2
+ammo = 2
+reload_seconds = 0.5
+
+[knife_thrower]
+slot = 2
+magazine = 3
+";
+
+fn world() -> World {
+    let map: GameMap = "\
+##########
+#P.......#
+##########
+".parse().unwrap();
+    let mut world = World::new(map, common::player(), common::archetypes(), 0);
+    world.set_weapons(WEAPONS.parse().unwrap());
+    world
+}
+
+fn shoot() -> InputSnapshot {
+    InputSnapshot { shoot: true, ..InputSnapshot::default() }
+}
+
+#[test]
+fn reloading_refills_from_the_spare_rounds() {
+    let mut ammo = Ammo::full(&Weapon { magazine: 5, ammo: Some(7), reload_seconds: 0.5, ..Weapon::default() });
+    for _ in 0..5 {
+        assert!(ammo.can_fire());
+        ammo.use_round();
+    }
+    assert!(ammo.is_empty());
+    assert!(!ammo.is_out());
+
+    assert!(ammo.reload());
+    assert!(!ammo.reload());
+    for _ in 0..29 {
+        assert!(!ammo.tick());
+    }
+    assert!(ammo.tick());
+    assert_eq!((ammo.loaded(), ammo.spare()), (5, Some(2)));
+
+    for _ in 0..5 {
+        ammo.use_round();
+    }
+    ammo.reload();
+    for _ in 0..30 {
+        ammo.tick();
+    }
+    assert_eq!((ammo.loaded(), ammo.spare()), (2, Some(0)));
+    ammo.use_round();
+    ammo.use_round();
+    assert!(ammo.is_out());
+    assert!(!ammo.reload());
+}
+
+#[test]
+fn weapons_without_a_magazine_never_run_out() {
+    let mut ammo = Ammo::default();
+    for _ in 0..1000 {
+        ammo.use_round();
+    }
+    assert!(ammo.can_fire());
+    assert!(!ammo.reload());
+}
+
+#[test]
+fn emptying_a_magazine_reloads_then_runs_dry() {
+    let mut world = world();
+    let mut events = Vec::new();
+    for _ in 0..120 {
+        events.extend(world.tick(&shoot()));
+    }
+    let ammo_events: Vec<GameEvent> = events.into_iter()
+        .filter(|event| matches!(event, GameEvent::MagazineEmpty { .. } | GameEvent::Reloaded))
+        .collect();
+    assert_eq!(ammo_events, vec![
+        GameEvent::MagazineEmpty { out_of_ammo: false },
+        GameEvent::Reloaded,
+        GameEvent::MagazineEmpty { out_of_ammo: true },
+    ]);
+    assert!(world.ammo().is_out());
+
+    // dry, the trigger does nothing: once the last shots are gone, none follow
+    while !world.bullets().is_empty() {
+        world.tick(&InputSnapshot::default());
+    }
+    for _ in 0..60 {
+        world.tick(&shoot());
+        assert!(world.bullets().is_empty());
+    }
+}
+
+#[test]
+fn reloading_takes_its_time_and_blocks_shooting() {
+    let mut world = world();
+    while world.ammo().loaded() == 2 {
+        world.tick(&shoot());
+    }
+    world.tick(&InputSnapshot { reload: true, ..InputSnapshot::default() });
+    assert!(world.ammo().is_reloading());
+
+    let fired = world.bullets().len();
+    for _ in 0..10 {
+        world.tick(&shoot());
+    }
+    assert_eq!(world.bullets().len(), fired);
+    assert!(world.ammo().is_reloading());
+
+    for _ in 0..30 {
+        world.tick(&InputSnapshot::default());
+    }
+    assert_eq!(world.ammo().loaded(), 2);
+    assert_eq!(world.ammo().spare(), Some(1));
+}
+
+#[test]
+fn switching_keeps_ammo_but_cancels_the_reload() {
+    let mut world = world();
+    while world.ammo().loaded() == 2 {
+        world.tick(&shoot());
+    }
+    world.tick(&InputSnapshot { reload: true, ..InputSnapshot::default() });
+    world.tick(&InputSnapshot { next_weapon: true, ..InputSnapshot::default() });
+    assert_eq!(world.weapon(), "knife_thrower");
+    assert_eq!(world.ammo().loaded(), 3);
+    assert_eq!(world.ammo().spare(), None);
+
+    world.tick(&InputSnapshot { previous_weapon: true, ..InputSnapshot::default() });
+    assert_eq!(world.weapon(), "revolver");
+    assert_eq!(world.ammo().loaded(), 1);
+    assert!(!world.ammo().is_reloading());
+}
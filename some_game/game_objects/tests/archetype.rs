@@ -0,0 +1,120 @@
+mod common;
+
+use quicksilver::geom::Vector;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use game_objects::archetype::{Archetypes, ArchetypeError};
+use game_objects::game_map::GameMap;
+use game_objects::game_object::Behavior;
+use game_objects::world::{World, InputSnapshot};
+
+const GAME_ENEMIES: &str = include_str!("../../game/enemies.toml");
+
+const SHOOTER: &str = r#"
+[shooter]
+size = [14.0, 14.0]
+speed = 0.1
+max_speed = 2.0
+range = 400.0
+health = 2
+sprite = "Circle"
+behavior = "shoot"
+shots_per_second = 2.0
+spawn_weight = 0
+"#;
+
+#[test]
+fn the_games_enemy_file_loads() {
+    let archetypes: Archetypes = GAME_ENEMIES.parse().unwrap();
+    for name in ["chaser", "shooter", "tank", "swarmer"] {
+        assert!(archetypes.get(name).is_some(), "{} is missing", name);
+    }
+}
+
+#[test]
+fn enemies_spawn_by_name_with_their_stats() {
+    let archetypes: Archetypes = GAME_ENEMIES.parse().unwrap();
+    let tank = archetypes.spawn("tank", Vector::new(64.0, 64.0)).unwrap();
+    let definition = archetypes.get("tank").unwrap();
+
+    assert_eq!(tank.size(), Vector::new(definition.size[0], definition.size[1]));
+    assert_eq!(tank.health(), definition.health);
+    assert_eq!(tank.max_speed(), definition.max_speed);
+    assert_eq!(tank.range(), definition.range);
+    assert_eq!(tank.behavior(), definition.behavior);
+    assert!(archetypes.spawn("dragon", Vector::new(0.0, 0.0)).is_none());
+}
+
+#[test]
+fn bad_definitions_are_rejected() {
+    let typo = SHOOTER.replace("max_speed", "max_sped");
+    assert!(matches!(typo.parse::<Archetypes>(), Err(ArchetypeError::Parse(_))));
+    assert!(matches!("".parse::<Archetypes>(), Err(ArchetypeError::Empty)));
+}
+
+#[test]
+fn shooters_need_a_positive_fire_rate() {
+    let missing = SHOOTER.replace("shots_per_second = 2.0\n", "");
+    for text in [missing, SHOOTER.replace("2.0", "0.0"), SHOOTER.replace("2.0", "-1.0"), SHOOTER.replace("2.0", "nan")] {
+        assert!(matches!(text.parse::<Archetypes>(), Err(ArchetypeError::Parse(_))), "{}", text);
+    }
+    // chasers never shoot, so they can leave it out
+    let chaser = SHOOTER.replace("\"shoot\"", "\"chase\"").replace("shots_per_second = 2.0\n", "");
+    assert!(chaser.parse::<Archetypes>().is_ok());
+}
+
+#[test]
+fn nonsense_stats_are_rejected() {
+    for (field, value) in [
+        ("health = 2", "health = 0"),
+        ("health = 2", "health = -5"),
+        ("size = [14.0, 14.0]", "size = [14.0, 0.0]"),
+        ("size = [14.0, 14.0]", "size = [-1.0, 14.0]"),
+        ("max_speed = 2.0", "max_speed = 0.0"),
+        ("max_speed = 2.0", "max_speed = nan"),
+    ] {
+        let text = SHOOTER.replace(field, value);
+        assert!(matches!(text.parse::<Archetypes>(), Err(ArchetypeError::Parse(message)) if message.starts_with("shooter")), "{}", value);
+    }
+    for chance in ["-0.1", "1.5", "nan"] {
+        let text = format!("{}\n[[shooter.loot]]\nchance = {}\nitem = \"gem\"\nscore = 1\n", SHOOTER, chance);
+        assert!(matches!(text.parse::<Archetypes>(), Err(ArchetypeError::Parse(_))), "{}", chance);
+    }
+}
+
+#[test]
+fn huge_spawn_weights_add_up_without_overflowing() {
+    let text = format!("{}\n{}", SHOOTER.replace("spawn_weight = 0", "spawn_weight = 4294967295"),
+        SHOOTER.replace("[shooter]", "[sniper]").replace("spawn_weight = 0", "spawn_weight = 4294967295"));
+    let archetypes: Archetypes = text.parse().unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        assert!(archetypes.choose(&mut rng).is_some());
+    }
+}
+
+#[test]
+fn spawn_weights_are_respected() {
+    let archetypes: Archetypes = SHOOTER.parse().unwrap();
+    assert!(archetypes.choose(&mut StdRng::seed_from_u64(0)).is_none());
+}
+
+#[test]
+fn shooters_shoot_the_player() {
+    let map: GameMap = "\
+############
+#P.........#
+############
+".parse().unwrap();
+    let mut world = World::new(map, common::player(), SHOOTER.parse().unwrap(), 0);
+    assert!(world.spawn_enemy("shooter", Vector::new(200.0, 40.0)));
+    assert_eq!(world.enemies()[0].behavior(), Behavior::Shoot);
+
+    let mut events = Vec::new();
+    for _ in 0..120 {
+        events.extend(world.tick(&InputSnapshot::default()));
+    }
+    assert!(!events.is_empty());
+    assert!(world.player().health() < world.player().max_health());
+}
@@ -0,0 +1,110 @@
+use quicksilver::geom::{Vector, Rectangle};
+
+use game_objects::GameObjectType;
+use game_objects::collision;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::{GameObject, WeaponState};
+use game_objects::sprite::SpriteId;
+
+// a one tile thick wall down the middle, x 160 to 192
+const THIN_WALL: &str = "\
+###########
+#....#....#
+#....#....#
+#....#....#
+###########
+";
+
+fn object(position: Vector, velocity: Vector, collidable: bool) -> GameObject {
+    GameObject::new(
+        position,
+        SpriteId::Circle,
+        Vector::new(12.0, 12.0),
+        velocity,
+        10_000.0,
+        WeaponState::Attack,
+        collidable,
+        GameObjectType::Projectile,
+    )
+}
+
+#[test]
+fn sweep_finds_the_first_contact() {
+    let moving = Rectangle::new(Vector::new(0.0, 0.0), Vector::new(10.0, 10.0));
+    let target = Rectangle::new(Vector::new(20.0, 0.0), Vector::new(10.0, 10.0));
+
+    let hit = collision::sweep(&moving, Vector::new(40.0, 0.0), &target).unwrap();
+    assert_eq!(hit.time, 0.25);
+    assert_eq!(hit.normal, Vector::new(-1.0, 0.0));
+    assert_eq!(hit.position, Vector::new(10.0, 0.0));
+
+    // too short, going the other way, and passing beside it
+    assert!(collision::sweep(&moving, Vector::new(5.0, 0.0), &target).is_none());
+    assert!(collision::sweep(&moving, Vector::new(-40.0, 0.0), &target).is_none());
+    assert!(collision::sweep(&moving, Vector::new(40.0, -40.0), &target).is_none());
+}
+
+#[test]
+fn sweep_ignores_what_it_starts_inside_or_slides_along() {
+    let wall = Rectangle::new(Vector::new(0.0, 10.0), Vector::new(100.0, 10.0));
+    let inside = Rectangle::new(Vector::new(10.0, 12.0), Vector::new(4.0, 4.0));
+    assert!(collision::sweep(&inside, Vector::new(5.0, 5.0), &wall).is_none());
+
+    let on_top = Rectangle::new(Vector::new(10.0, 0.0), Vector::new(10.0, 10.0));
+    assert!(collision::sweep(&on_top, Vector::new(50.0, 0.0), &wall).is_none());
+    assert_eq!(collision::sweep(&on_top, Vector::new(5.0, 5.0), &wall).unwrap().time, 0.0);
+}
+
+#[test]
+fn fast_projectiles_never_skip_a_one_tile_wall() {
+    let map: GameMap = THIN_WALL.parse().unwrap();
+    for speed in [8.0, 20.0, 31.0, 45.0, 64.0, 100.0, 250.0] {
+        for start_x in [40.0, 77.5, 101.0, 140.0] {
+            let mut bullet = object(Vector::new(start_x, 70.0), Vector::new(speed, 1.5), false);
+            let mut stopped = false;
+            for _ in 0..100 {
+                if bullet.carry_projectile(&map).is_some() {
+                    stopped = true;
+                    break;
+                }
+            }
+            assert!(stopped, "speed {} from {} never hit the wall", speed, start_x);
+            assert_eq!(bullet.position().x, 160.0 - 12.0);
+        }
+    }
+}
+
+#[test]
+fn fast_bodies_stop_flush_against_a_one_tile_wall() {
+    let map: GameMap = THIN_WALL.parse().unwrap();
+    for speed in [4.0, 33.0, 70.0, 300.0] {
+        let mut body = object(Vector::new(40.0, 70.0), Vector::new(speed, 0.0), true);
+        for _ in 0..50 {
+            body.carry_momentum(&map);
+        }
+        assert_eq!(body.position().x, 160.0 - 12.0);
+        assert_eq!(body.velocity().x, 0.0);
+    }
+}
+
+#[test]
+fn bodies_slide_along_walls() {
+    let map: GameMap = THIN_WALL.parse().unwrap();
+    let mut body = object(Vector::new(140.0, 40.0), Vector::new(30.0, 10.0), true);
+    body.carry_momentum(&map);
+    assert_eq!(body.position(), Vector::new(148.0, 50.0));
+    assert_eq!(body.velocity(), Vector::new(0.0, 10.0));
+}
+
+#[test]
+fn bullets_hit_small_targets_they_fly_past() {
+    let map: GameMap = THIN_WALL.parse().unwrap();
+    let target = Rectangle::new(Vector::new(80.0, 70.0), Vector::new(4.0, 12.0));
+    let mut bullet = object(Vector::new(40.0, 70.0), Vector::new(60.0, 0.0), false);
+    bullet.remember_position();
+    bullet.carry_projectile(&map);
+    // it ended up well past the target without ever overlapping it at the end of a tick
+    assert!(bullet.position().x > 90.0);
+    assert!(bullet.passed_through(&target));
+    assert!(!bullet.passed_through(&Rectangle::new(Vector::new(80.0, 100.0), Vector::new(4.0, 12.0))));
+}
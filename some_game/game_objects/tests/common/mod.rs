@@ -0,0 +1,31 @@
+// Fixtures shared by the tests that run a World. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use quicksilver::geom::Vector;
+
+use game_objects::archetype::{Archetype, Archetypes};
+use game_objects::game_object::GameObject;
+use game_objects::sprite::{SpriteId, DirectionalSprites};
+
+// facing right with the pistol, World::new puts it on the map's player spawn
+pub fn player() -> GameObject {
+    GameObject::new_with_weapon(
+        Vector::new(0.0, 0.0),
+        DirectionalSprites::all(SpriteId::ArrowRight),
+        SpriteId::Circle,
+    )
+}
+
+// the default chaser, but never picked for random spawns
+pub fn chaser() -> Archetype {
+    let mut chaser = Archetypes::default().get("chaser").unwrap().clone();
+    chaser.spawn_weight = 0;
+    chaser
+}
+
+// no random spawns, so only the enemies a test places are there
+pub fn archetypes() -> Archetypes {
+    let mut archetypes = Archetypes::default();
+    archetypes.insert("chaser", chaser());
+    archetypes
+}
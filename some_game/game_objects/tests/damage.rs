@@ -0,0 +1,112 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::GameObjectType;
+use game_objects::events::GameEvent;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::sprite::SpriteId;
+use game_objects::world::{World, InputSnapshot};
+
+// 3 health, hurt again at most every 10 ticks
+fn enemy() -> GameObject {
+    let mut enemy = GameObject::new_enemy(Vector::new(0.0, 0.0), SpriteId::Circle);
+    enemy.set_max_health(3);
+    enemy.set_invulnerability(10);
+    enemy
+}
+
+fn wait_out_invulnerability(object: &mut GameObject) {
+    while object.is_invulnerable() {
+        object.update_timers();
+    }
+}
+
+#[test]
+fn it_takes_several_hits_to_kill() {
+    let mut enemy = enemy();
+
+    for health_left in [2, 1] {
+        let event = enemy.take_damage(1);
+        assert!(matches!(event, Some(GameEvent::Damaged { kind: GameObjectType::Enemy, amount: 1, .. })));
+        assert_eq!(enemy.health(), health_left);
+        assert!(!enemy.is_dead());
+        wait_out_invulnerability(&mut enemy);
+    }
+
+    assert!(matches!(enemy.take_damage(1), Some(GameEvent::Died { kind: GameObjectType::Enemy, .. })));
+    assert!(enemy.is_dead());
+}
+
+#[test]
+fn hits_during_invulnerability_are_ignored() {
+    let mut enemy = enemy();
+    assert!(enemy.take_damage(1).is_some());
+    assert_eq!(enemy.invulnerable_frames(), 10);
+
+    for _ in 0..9 {
+        enemy.update_timers();
+        assert!(enemy.take_damage(1).is_none());
+    }
+    assert_eq!(enemy.health(), 2);
+
+    enemy.update_timers();
+    assert!(!enemy.is_invulnerable());
+    assert!(enemy.take_damage(1).is_some());
+    assert_eq!(enemy.health(), 1);
+}
+
+#[test]
+fn death_is_reported_once_and_the_dead_take_no_damage() {
+    let mut enemy = enemy();
+    enemy.set_invulnerability(0);
+
+    let mut deaths = 0;
+    for _ in 0..10 {
+        if let Some(GameEvent::Died { position, .. }) = enemy.take_damage(2) {
+            assert_eq!(position, enemy.center());
+            deaths += 1;
+        }
+        enemy.update_timers();
+    }
+    assert_eq!(deaths, 1);
+    // the killing hit lands in full, nothing after it does
+    assert_eq!(enemy.health(), -1);
+    assert!(enemy.take_damage(5).is_none());
+    assert_eq!(enemy.health(), -1);
+}
+
+#[test]
+fn bullets_are_spent_on_an_invulnerable_enemy() {
+    // big enough to catch a whole shotgun blast, and invulnerable after the first pellet
+    let mut shield = common::chaser();
+    shield.size = [24.0, 24.0];
+    shield.health = 10;
+    shield.invulnerability = 30;
+    shield.range = 1.0;
+    let mut archetypes = common::archetypes();
+    archetypes.insert("shield", shield);
+    let map = "\
+####################
+#P.................#
+####################
+";
+    let mut world = World::new(map.parse::<GameMap>().unwrap(), common::player(), archetypes, 0);
+    world.set_weapons("[shotgun]\nprojectiles = 6\nspread = 40.0\nspeed = 7.0\nsize = [6.0, 6.0]".parse().unwrap());
+    world.spawn_enemy("shield", Vector::new(100.0, 36.0));
+    // in the line of fire behind it
+    world.spawn_enemy("chaser", Vector::new(200.0, 42.0));
+
+    let shoot = InputSnapshot { shoot: true, ..InputSnapshot::default() };
+    while world.bullets().is_empty() {
+        world.tick(&shoot);
+    }
+    assert_eq!(world.bullets().len(), 6);
+    for _ in 0..10 {
+        world.tick(&InputSnapshot::default());
+    }
+    assert!(world.bullets().is_empty());
+    assert_eq!(world.enemies()[0].health(), 9);
+    assert_eq!(world.enemies()[1].health(), world.enemies()[1].max_health());
+}
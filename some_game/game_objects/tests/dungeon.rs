@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+
+use game_objects::dungeon::{self, DungeonParams, DungeonStyle};
+use game_objects::map_layout::MapLayout;
+
+const SEEDS: [u64; 6] = [0, 1, 2, 7, 42, 1234];
+const SIZES: [(usize, usize); 3] = [(20, 15), (40, 30), (80, 50)];
+
+fn params(style: DungeonStyle) -> DungeonParams {
+    DungeonParams { style, ..DungeonParams::default() }
+}
+
+// every generated map the tests look at, with what made it
+fn maps() -> Vec<(DungeonStyle, u64, (usize, usize), MapLayout)> {
+    let mut maps = Vec::new();
+    for style in [DungeonStyle::Rooms, DungeonStyle::Caves] {
+        for seed in SEEDS {
+            for (width, height) in SIZES {
+                maps.push((style, seed, (width, height), dungeon::generate(seed, width, height, &params(style))));
+            }
+        }
+    }
+    maps
+}
+
+fn floor_cells(layout: &MapLayout) -> Vec<(usize, usize)> {
+    (0..layout.height())
+        .flat_map(|y| (0..layout.width()).map(move |x| (x, y)))
+        .filter(|cell| layout.is_walkable(cell.0, cell.1))
+        .collect()
+}
+
+// floor cells reachable from start, stepping up, down, left and right
+fn reachable(layout: &MapLayout, start: (usize, usize)) -> usize {
+    let width = layout.width();
+    let mut seen = vec![false; width * layout.height()];
+    let mut queue = VecDeque::from([start]);
+    seen[start.1 * width + start.0] = true;
+    let mut count = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        count += 1;
+        for (next_x, next_y) in [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)] {
+            if layout.is_walkable(next_x, next_y) && !seen[next_y * width + next_x] {
+                seen[next_y * width + next_x] = true;
+                queue.push_back((next_x, next_y));
+            }
+        }
+    }
+    count
+}
+
+#[test]
+fn the_same_seed_gives_the_same_map() {
+    for (style, seed, (width, height), layout) in maps() {
+        let again = dungeon::generate(seed, width, height, &params(style));
+        assert_eq!(floor_cells(&layout), floor_cells(&again), "seed {} at {}x{}", seed, width, height);
+        assert_eq!(layout.player_spawn(), again.player_spawn());
+        assert_eq!(layout.enemy_spawns(), again.enemy_spawns());
+    }
+}
+
+#[test]
+fn different_seeds_give_different_maps() {
+    for style in [DungeonStyle::Rooms, DungeonStyle::Caves] {
+        let first = dungeon::generate(1, 40, 30, &params(style));
+        let second = dungeon::generate(2, 40, 30, &params(style));
+        assert_ne!(floor_cells(&first), floor_cells(&second));
+    }
+}
+
+#[test]
+fn all_floor_is_connected() {
+    for (_, seed, (width, height), layout) in maps() {
+        let floor = floor_cells(&layout);
+        assert!(!floor.is_empty(), "seed {} at {}x{} has no floor", seed, width, height);
+        assert_eq!(reachable(&layout, floor[0]), floor.len(), "seed {} at {}x{}", seed, width, height);
+    }
+}
+
+#[test]
+fn the_outer_edge_is_wall() {
+    for (_, _, (width, height), layout) in maps() {
+        for x in 0..width {
+            assert!(!layout.is_walkable(x, 0) && !layout.is_walkable(x, height - 1));
+        }
+        for y in 0..height {
+            assert!(!layout.is_walkable(0, y) && !layout.is_walkable(width - 1, y));
+        }
+    }
+}
+
+#[test]
+fn spawns_are_on_floor() {
+    for (_, seed, (width, height), layout) in maps() {
+        let player = layout.player_spawn().unwrap();
+        assert!(layout.is_walkable(player.0, player.1), "seed {} at {}x{}", seed, width, height);
+
+        let enemies = layout.enemy_spawns();
+        assert!(!enemies.is_empty());
+        assert!(enemies.len() <= DungeonParams::default().enemy_spawns);
+        for enemy in enemies {
+            assert!(layout.is_walkable(enemy.0, enemy.1));
+            assert_ne!(*enemy, player);
+        }
+    }
+}
+
+#[test]
+fn maps_without_an_interior_are_solid_wall() {
+    for style in [DungeonStyle::Rooms, DungeonStyle::Caves] {
+        let layout = dungeon::generate(0, 2, 2, &params(style));
+        assert!(floor_cells(&layout).is_empty());
+        assert_eq!(layout.player_spawn(), None);
+    }
+}
+
+#[test]
+fn out_of_range_wall_chances_are_clamped() {
+    let caves = |wall_chance: f64| {
+        let params = DungeonParams { wall_chance, ..params(DungeonStyle::Caves) };
+        floor_cells(&dungeon::generate(3, 40, 30, &params))
+    };
+    // all wall, with nowhere to put anything
+    assert!(caves(f64::INFINITY).is_empty());
+    assert_eq!(caves(2.0), caves(1.0));
+    // open floor, smoothed only where it meets the outer wall
+    assert!(caves(0.0).len() > 36 * 26);
+    assert_eq!(caves(f64::NEG_INFINITY), caves(0.0));
+    assert_eq!(caves(-1.0), caves(0.0));
+    // NaN falls back to the default chance
+    assert_eq!(caves(f64::NAN), caves(DungeonParams::default().wall_chance));
+}
@@ -0,0 +1,81 @@
+use quicksilver::geom::Vector;
+
+use game_objects::flow_field::FlowField;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::map_layout::MapLayout;
+use game_objects::pathfinding::find_path;
+use game_objects::sprite::SpriteId;
+
+fn layout(level: &str) -> MapLayout {
+    level.parse().unwrap()
+}
+
+#[test]
+fn flow_fields_lead_downhill_to_the_target() {
+    let layout = layout("\
+#######
+#..#..#
+#..#..#
+#.....#
+#######
+");
+    let mut flow_field = FlowField::new();
+    assert!(flow_field.update(&layout, (5, 1)));
+    assert_eq!(flow_field.cost((5, 1)), Some(0));
+    assert_eq!(flow_field.cost((3, 1)), None);
+
+    // walking downhill from anywhere ends on the target
+    let mut cell = (1, 1);
+    let mut steps = 0;
+    while let Some(next) = flow_field.next_cell(&layout, cell) {
+        assert!(flow_field.cost(next) < flow_field.cost(cell));
+        cell = next;
+        steps += 1;
+    }
+    assert_eq!(cell, (5, 1));
+    // and takes exactly as many steps as A*
+    assert_eq!(steps + 1, find_path(&layout, (1, 1), (5, 1)).unwrap().len());
+}
+
+#[test]
+fn flow_fields_only_rebuild_for_a_new_target() {
+    let layout = layout("\
+#####
+#...#
+#####
+");
+    let mut flow_field = FlowField::new();
+    assert!(flow_field.update(&layout, (1, 1)));
+    assert!(!flow_field.update(&layout, (1, 1)));
+    assert!(flow_field.update(&layout, (3, 1)));
+    assert_eq!(flow_field.target(), Some((3, 1)));
+}
+
+#[test]
+fn enemies_walk_around_a_wall_to_reach_their_destination() {
+    let map: GameMap = "\
+##########
+#....#...#
+#....#...#
+#....#...#
+#........#
+##########
+".parse().unwrap();
+    let destination = MapLayout::cell_center((1, 1));
+    let mut flow_field = FlowField::new();
+    flow_field.update(map.layout(), (1, 1));
+    let mut enemy = GameObject::new_enemy(Vector::new(7.0 * 32.0 + 10.0, 32.0 + 10.0), SpriteId::Circle);
+
+    let mut reached = false;
+    for _ in 0..600 {
+        enemy.move_around_walls_to(destination, &map, &flow_field);
+        enemy.steer();
+        enemy.carry_momentum(&map);
+        if (enemy.center() - destination).len() < 16.0 {
+            reached = true;
+            break;
+        }
+    }
+    assert!(reached);
+}
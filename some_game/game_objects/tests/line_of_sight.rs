@@ -0,0 +1,92 @@
+use quicksilver::geom::Vector;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use game_objects::flow_field::FlowField;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::map_layout::MapLayout;
+use game_objects::sprite::SpriteId;
+
+const CORRIDORS: &str = "\
+##########
+#........#
+#.######.#
+#........#
+##########
+";
+
+fn map() -> GameMap {
+    CORRIDORS.parse().unwrap()
+}
+
+fn center(cell: (usize, usize)) -> Vector {
+    MapLayout::cell_center(cell)
+}
+
+#[test]
+fn open_lines_are_clear() {
+    let map = map();
+    assert!(map.has_line_of_sight(center((1, 1)), center((8, 1))));
+    assert!(map.has_line_of_sight(center((1, 1)), center((1, 3))));
+    assert!(map.has_line_of_sight(center((8, 3)), center((8, 3))));
+}
+
+#[test]
+fn walls_block_sight_at_the_first_one_hit() {
+    let map = map();
+    assert!(!map.has_line_of_sight(center((1, 1)), center((8, 3))));
+    assert_eq!(map.raycast(center((4, 0)) + Vector::new(0.0, 40.0), center((4, 3))), Some((4, 2)));
+    assert_eq!(map.raycast(center((1, 3)), center((8, 1))), Some((3, 2)));
+}
+
+#[test]
+fn sight_cannot_squeeze_through_a_corner() {
+    let map: GameMap = "\
+####
+#.##
+##.#
+####
+".parse().unwrap();
+    // exactly through the point where the two floor tiles touch
+    assert!(!map.has_line_of_sight(center((1, 1)), center((2, 2))));
+}
+
+#[test]
+fn enemies_dont_see_through_walls() {
+    let map = map();
+    let enemy = GameObject::new_enemy(center((1, 1)), SpriteId::Circle);
+    let visible = GameObject::new_enemy(center((6, 1)), SpriteId::Circle);
+    let hidden = GameObject::new_enemy(center((3, 3)), SpriteId::Circle);
+    assert!(enemy.in_range(&hidden));
+    assert!(enemy.can_see(&visible, &map));
+    assert!(!enemy.can_see(&hidden, &map));
+}
+
+#[test]
+fn enemies_search_where_the_target_was_last_seen() {
+    let map = map();
+    let flow_field = FlowField::new();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut enemy = GameObject::new_enemy(center((1, 1)) - Vector::new(6.0, 6.0), SpriteId::Circle);
+    let mut target = GameObject::new_enemy(center((8, 1)) - Vector::new(6.0, 6.0), SpriteId::Circle);
+
+    enemy.patrol_for(&target, &map, &flow_field, &mut rng);
+    assert_eq!(enemy.last_known_position(), Some(target.center()));
+
+    // the target slips round the corner while the enemy is still far away
+    target.set_position(center((8, 3)) - Vector::new(6.0, 6.0));
+    assert!(!enemy.can_see(&target, &map));
+
+    let mut spotted = false;
+    for _ in 0..600 {
+        enemy.patrol_for(&target, &map, &flow_field, &mut rng);
+        enemy.steer();
+        enemy.carry_momentum(&map);
+        if enemy.can_see(&target, &map) {
+            spotted = true;
+            break;
+        }
+    }
+    assert!(spotted);
+}
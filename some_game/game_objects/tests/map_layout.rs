@@ -0,0 +1,83 @@
+use game_objects::GameObjectType;
+use game_objects::map_layout::{MapLayout, MapError};
+
+#[test]
+fn parses_tiles_and_spawns() {
+    let layout: MapLayout = "\
+#####
+#P.E#
+#####
+".parse().unwrap();
+    assert_eq!(layout.width(), 5);
+    assert_eq!(layout.height(), 3);
+    assert_eq!(layout.player_spawn(), Some((1, 1)));
+    assert_eq!(layout.enemy_spawns(), &vec![(3, 1)]);
+    assert!(layout.tile(0, 0).unwrap().collidable);
+    // spawn markers stand on floor
+    let spawn = layout.tile(1, 1).unwrap();
+    assert!(spawn.kind == GameObjectType::Floor && !spawn.collidable);
+}
+
+#[test]
+fn windows_line_endings_and_trailing_blank_lines_are_ignored() {
+    let layout: MapLayout = "###\r\n#P#\r\n###\r\n\r\n\n".parse().unwrap();
+    assert_eq!(layout.width(), 3);
+    assert_eq!(layout.height(), 3);
+    assert_eq!(layout.player_spawn(), Some((1, 1)));
+}
+
+#[test]
+fn empty_levels_are_rejected() {
+    assert!(matches!("".parse::<MapLayout>(), Err(MapError::Empty)));
+    assert!(matches!("\n\n".parse::<MapLayout>(), Err(MapError::Empty)));
+}
+
+#[test]
+fn unknown_tiles_report_their_line_and_column() {
+    let result = "\
+#####
+#P..#
+#.?.#
+#####
+".parse::<MapLayout>();
+    assert!(matches!(result, Err(MapError::UnknownTile { line: 3, column: 3, found: '?' })));
+
+    // columns count characters, not bytes
+    let result = "#é#".parse::<MapLayout>();
+    assert!(matches!(result, Err(MapError::UnknownTile { line: 1, column: 2, found: 'é' })));
+}
+
+#[test]
+fn ragged_rows_report_their_line_and_widths() {
+    let result = "\
+#####
+#P..#
+#..#
+#####
+".parse::<MapLayout>();
+    assert!(matches!(result, Err(MapError::RaggedRow { line: 3, expected: 5, found: 4 })));
+
+    let result = "\
+###
+#P#
+####
+".parse::<MapLayout>();
+    assert!(matches!(result, Err(MapError::RaggedRow { line: 3, expected: 3, found: 4 })));
+}
+
+#[test]
+fn a_second_player_spawn_reports_where_it_is() {
+    let result = "\
+######
+#P...#
+#..P.#
+######
+".parse::<MapLayout>();
+    assert!(matches!(result, Err(MapError::DuplicatePlayerSpawn { line: 3, column: 4 })));
+}
+
+#[test]
+fn errors_name_their_position() {
+    let err = "#?#".parse::<MapLayout>().err().unwrap();
+    assert_eq!(err.to_string(), "line 1, column 2: unknown tile '?'");
+}
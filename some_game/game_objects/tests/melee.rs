@@ -0,0 +1,113 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::archetype::Archetypes;
+use game_objects::events::GameEvent;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::WeaponState;
+use game_objects::melee::{Melee, SWING_FRAMES, SWING_ARC};
+use game_objects::world::{World, InputSnapshot};
+
+const ROOM: &str = "\
+############
+#P.........#
+#..........#
+#..........#
+############
+";
+
+// the player faces right from (32, 32), so its weapon swings around (82, 48)
+fn world() -> World {
+    // enemies that stand still, don't spawn by themselves and take every hit
+    let mut archetypes = Archetypes::default();
+    let mut dummy = common::chaser();
+    dummy.range = 1.0;
+    dummy.invulnerability = 0;
+    dummy.health = 10;
+    archetypes.insert("dummy", dummy);
+    World::new(ROOM.parse::<GameMap>().unwrap(), common::player(), archetypes, 0)
+}
+
+fn hits(events: &[GameEvent]) -> usize {
+    events.iter().filter(|event| matches!(event, GameEvent::Damaged { .. })).count()
+}
+
+#[test]
+fn swings_sweep_the_arc_then_cool_down() {
+    let mut melee = Melee::new(2.0, 1, 1.0);
+    for _ in 0..30 {
+        melee.tick();
+    }
+    assert!(melee.start());
+    assert!(!melee.start());
+    assert_eq!(melee.angle(), -SWING_ARC.to_radians() / 2.0);
+
+    let mut last = melee.angle();
+    for _ in 1..SWING_FRAMES {
+        melee.tick();
+        assert!(melee.angle() > last);
+        last = melee.angle();
+    }
+    melee.tick();
+    assert!(!melee.is_swinging());
+    assert!(!melee.start());
+    for _ in 0..30 {
+        melee.tick();
+    }
+    assert!(melee.start());
+    assert_eq!(melee.swings(), 2);
+}
+
+#[test]
+fn the_weapon_attacks_only_while_swinging() {
+    let mut world = world();
+    let melee = InputSnapshot { melee: true, ..InputSnapshot::default() };
+    while !world.player().is_swinging() {
+        world.tick(&melee);
+    }
+    assert!(world.player().weapon().state() == WeaponState::Attack);
+    for _ in 0..SWING_FRAMES {
+        world.tick(&InputSnapshot::default());
+    }
+    assert!(!world.player().is_swinging());
+    assert!(world.player().weapon().state() == WeaponState::Default);
+}
+
+#[test]
+fn a_swing_hits_once_and_knocks_back() {
+    let mut world = world();
+    world.spawn_enemy("dummy", Vector::new(76.0, 42.0));
+
+    let mut events = Vec::new();
+    let melee = InputSnapshot { melee: true, ..InputSnapshot::default() };
+    while !world.player().is_swinging() {
+        events.extend(world.tick(&melee));
+    }
+    for _ in 0..SWING_FRAMES {
+        events.extend(world.tick(&InputSnapshot::default()));
+    }
+
+    assert_eq!(hits(&events), 1);
+    let enemy = &world.enemies()[0];
+    assert_eq!(enemy.health(), enemy.max_health() - Melee::default().damage());
+    assert!(enemy.position().x > 90.0);
+}
+
+#[test]
+fn swings_miss_what_is_behind() {
+    let mut world = world();
+    // up against the right wall, still facing it
+    for _ in 0..200 {
+        world.tick(&InputSnapshot { move_right: true, ..InputSnapshot::default() });
+    }
+    assert_eq!(world.player().velocity().x, 0.0);
+    let behind = world.player().position() - Vector::new(40.0, 0.0);
+    world.spawn_enemy("dummy", behind);
+
+    let mut events = Vec::new();
+    for _ in 0..60 {
+        events.extend(world.tick(&InputSnapshot { melee: true, ..InputSnapshot::default() }));
+    }
+    assert_eq!(hits(&events), 0);
+}
@@ -0,0 +1,59 @@
+use game_objects::map_layout::MapLayout;
+use game_objects::pathfinding::find_path;
+
+fn layout(level: &str) -> MapLayout {
+    level.parse().unwrap()
+}
+
+#[test]
+fn open_ground_goes_diagonally() {
+    let layout = layout("\
+#####
+#...#
+#...#
+#...#
+#####
+");
+    let path = find_path(&layout, (1, 1), (3, 3)).unwrap();
+    assert_eq!(path, vec![(1, 1), (2, 2), (3, 3)]);
+}
+
+#[test]
+fn paths_never_cut_wall_corners() {
+    let layout = layout("\
+####
+#..#
+#.##
+####
+");
+    // (2, 1) -> (1, 2) squeezes past the wall at (2, 2)
+    let path = find_path(&layout, (2, 1), (1, 2)).unwrap();
+    assert_eq!(path, vec![(2, 1), (1, 1), (1, 2)]);
+}
+
+#[test]
+fn paths_go_around_walls() {
+    let layout = layout("\
+#######
+#..#..#
+#..#..#
+#.....#
+#######
+");
+    let path = find_path(&layout, (1, 1), (5, 1)).unwrap();
+    assert_eq!(path.first(), Some(&(1, 1)));
+    assert_eq!(path.last(), Some(&(5, 1)));
+    assert!(path.iter().all(|(x, y)| layout.is_walkable(*x, *y)));
+    assert!(path.contains(&(3, 3)));
+}
+
+#[test]
+fn sealed_rooms_have_no_path() {
+    let layout = layout("\
+#####
+#.#.#
+#####
+");
+    assert!(find_path(&layout, (1, 1), (3, 1)).is_none());
+    assert!(find_path(&layout, (0, 0), (3, 1)).is_none());
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::events::GameEvent;
+use game_objects::game_map::GameMap;
+use game_objects::pickup::{Item, PickupKind};
+use game_objects::world::{World, InputSnapshot};
+
+// enemies that stand still and can't spawn on their own, the dummy drops a gem
+// when killed and the crate a shotgun
+const DUMMY: &str = r#"
+[dummy]
+size = [12.0, 12.0]
+speed = 0.0
+max_speed = 1.0
+range = 0.0
+health = 1
+damage = 1
+sprite = "Circle"
+behavior = "chase"
+invulnerability = 0
+spawn_weight = 0
+
+[[dummy.loot]]
+chance = 1.0
+item = "gem"
+score = 3
+
+[crate]
+size = [12.0, 12.0]
+speed = 0.0
+max_speed = 1.0
+range = 0.0
+health = 1
+sprite = "Wall"
+behavior = "chase"
+spawn_weight = 0
+
+[[crate.loot]]
+chance = 1.0
+item = "weapon"
+name = "shotgun"
+"#;
+
+fn world(map: &str) -> World {
+    let map: GameMap = map.parse().unwrap();
+    World::new(map, common::player(), DUMMY.parse().unwrap(), 0)
+}
+
+fn walk_right(world: &mut World, ticks: u32) -> Vec<GameEvent> {
+    let right = InputSnapshot { move_right: true, ..InputSnapshot::default() };
+    (0..ticks).flat_map(|_| world.tick(&right)).collect()
+}
+
+#[test]
+fn items_parse_from_their_names() {
+    assert_eq!("health 2".parse::<Item>(), Ok(Item::Health { amount: 2 }));
+    assert_eq!("ammo".parse::<Item>(), Ok(Item::Ammo { rounds: 10 }));
+    assert_eq!("weapon shotgun".parse::<Item>(), Ok(Item::Weapon { name: "shotgun".to_string() }));
+    assert_eq!("gem 5".parse::<Item>(), Ok(Item::Gem { score: 5 }));
+    for bad in ["", "cake", "gem lots", "weapon", "health 1 2", "health 0", "health -3", "health 3000000000"] {
+        assert!(bad.parse::<Item>().is_err(), "{} parsed", bad);
+    }
+}
+
+#[test]
+fn walking_over_a_gem_scores_it() {
+    let mut world = world("\
+##########
+#P..G....#
+##########
+");
+    assert_eq!(world.pickups().len(), 1);
+
+    let events = walk_right(&mut world, 120);
+
+    assert!(world.pickups().is_empty());
+    assert_eq!(world.score(), 1);
+    assert_eq!(events.iter().filter(|event| matches!(event, GameEvent::PickedUp { kind: PickupKind::Gem, .. })).count(), 1);
+}
+
+#[test]
+fn health_is_left_lying_at_full_health() {
+    let map = "\
+##########
+#P..H....#
+##########
+";
+    let mut healthy = world(map);
+    walk_right(&mut healthy, 120);
+    assert_eq!(healthy.pickups().len(), 1);
+
+    // a dummy on the spawn hurts the player once before they walk off
+    let mut hurt = world(map);
+    let spawn = hurt.player().position();
+    assert!(hurt.spawn_enemy("dummy", spawn));
+    hurt.tick(&InputSnapshot::default());
+    assert_eq!(hurt.player().health(), hurt.player().max_health() - 1);
+
+    walk_right(&mut hurt, 120);
+    assert!(hurt.pickups().is_empty());
+    assert_eq!(hurt.player().health(), hurt.player().max_health());
+}
+
+#[test]
+fn killed_enemies_drop_their_loot() {
+    let mut world = world("\
+####################
+#P.................#
+####################
+");
+    let spawn = world.player().position() + Vector::new(160.0, 0.0);
+    assert!(world.spawn_enemy("dummy", spawn));
+
+    let shoot = InputSnapshot { shoot: true, ..InputSnapshot::default() };
+    for _ in 0..120 {
+        world.tick(&shoot);
+        if world.enemies().is_empty() {
+            break;
+        }
+    }
+    assert!(world.enemies().is_empty());
+    assert_eq!(world.pickups().len(), 1);
+    assert_eq!(world.pickups()[0].item(), &Item::Gem { score: 3 });
+
+    walk_right(&mut world, 240);
+    assert_eq!(world.score(), 3);
+}
+
+#[test]
+fn the_magnet_pulls_pickups_in() {
+    let map = "\
+##########
+#P..G....#
+##########
+";
+    let mut still = world(map);
+    for _ in 0..60 {
+        still.tick(&InputSnapshot::default());
+    }
+    assert_eq!(still.pickups().len(), 1);
+
+    let mut magnetised = world(map);
+    magnetised.set_magnet_radius(128.0);
+    for _ in 0..60 {
+        magnetised.tick(&InputSnapshot::default());
+    }
+    assert!(magnetised.pickups().is_empty());
+    assert_eq!(magnetised.score(), 1);
+}
+
+#[test]
+fn weapon_pickups_unlock_locked_weapons() {
+    let mut world = world("\
+####################
+#P..A..............#
+####################
+");
+    world.set_weapons("
+[pistol]
+slot = 1
+
+[shotgun]
+slot = 2
+magazine = 6
+ammo = 12
+locked = true
+".parse().unwrap());
+
+    // can't switch to what you don't have
+    world.tick(&InputSnapshot { next_weapon: true, ..InputSnapshot::default() });
+    assert_eq!(world.weapon(), "pistol");
+    assert!(!world.owns("shotgun"));
+
+    let spawn = world.player().position() + Vector::new(320.0, 0.0);
+    assert!(world.spawn_enemy("crate", spawn));
+    let shoot = InputSnapshot { shoot: true, ..InputSnapshot::default() };
+    for _ in 0..120 {
+        world.tick(&shoot);
+        if world.enemies().is_empty() {
+            break;
+        }
+    }
+    assert_eq!(world.pickups().len(), 2);
+
+    // the pistol has endless ammo, so the ammo is left for the shotgun
+    walk_right(&mut world, 240);
+    assert!(world.owns("shotgun"));
+    assert_eq!(world.weapon(), "shotgun");
+    assert_eq!(world.pickups().len(), 1);
+
+    let left = InputSnapshot { move_left: true, ..InputSnapshot::default() };
+    for _ in 0..240 {
+        world.tick(&left);
+    }
+    assert!(world.pickups().is_empty());
+    assert_eq!(world.ammo().spare(), Some(22));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::GameObjectType;
+use game_objects::archetype::Archetypes;
+use game_objects::events::GameEvent;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::{GameObject, WeaponState};
+use game_objects::sprite::SpriteId;
+use game_objects::world::{World, InputSnapshot};
+
+const CORRIDOR: &str = "\
+########
+#......#
+########
+";
+
+fn bullet(position: Vector, velocity: Vector) -> GameObject {
+    GameObject::new(
+        position,
+        SpriteId::Circle,
+        Vector::new(12.0, 12.0),
+        velocity,
+        1000.0,
+        WeaponState::Attack,
+        false,
+        GameObjectType::Projectile,
+    )
+}
+
+#[test]
+fn bullets_stop_at_walls() {
+    let map: GameMap = CORRIDOR.parse().unwrap();
+    let mut bullet = bullet(Vector::new(180.0, 40.0), Vector::new(8.0, 0.0));
+    let mut impact = None;
+    for _ in 0..20 {
+        if let Some(event) = bullet.carry_projectile(&map) {
+            impact = Some(event);
+            break;
+        }
+    }
+    match impact {
+        Some(GameEvent::Impact { position }) => assert!(position.x > 192.0),
+        _ => panic!("the bullet never hit the wall"),
+    }
+    assert!(bullet.sprite().pos.x + bullet.size().x <= 224.0);
+}
+
+#[test]
+fn ricochets_bounce_until_used_up() {
+    let map: GameMap = CORRIDOR.parse().unwrap();
+    let mut bullet = bullet(Vector::new(180.0, 40.0), Vector::new(8.0, 0.0));
+    bullet.set_ricochets(1);
+
+    let mut events = Vec::new();
+    for _ in 0..60 {
+        events.extend(bullet.carry_projectile(&map));
+        if matches!(events.last(), Some(GameEvent::Impact { .. })) {
+            break;
+        }
+    }
+    assert!(matches!(events[0], GameEvent::Ricochet { .. }));
+    assert!(matches!(events[1], GameEvent::Impact { .. }));
+    assert_eq!(bullet.ricochets(), 0);
+    // the second wall is the one on the left
+    assert!(bullet.position().x < 64.0);
+}
+
+#[test]
+fn world_removes_bullets_that_hit_walls() {
+    let map: GameMap = "\
+######
+#P...#
+######
+".parse().unwrap();
+    let mut world = World::new(map, common::player(), common::archetypes(), 0);
+
+    let mut impacts = 0;
+    for tick in 0..90 {
+        let input = InputSnapshot { shoot: tick < 60, ..InputSnapshot::default() };
+        impacts += world.tick(&input).iter()
+            .filter(|event| matches!(event, GameEvent::Impact { .. }))
+            .count();
+    }
+    assert_eq!(impacts, 1);
+    assert!(world.bullets().is_empty());
+}
+
+#[test]
+fn shots_ricochet_as_often_as_the_weapon_says() {
+    let archetypes: Archetypes = "
+[bouncer]
+size = [12.0, 12.0]
+speed = 0.1
+max_speed = 2.0
+range = 300.0
+health = 1
+sprite = \"Circle\"
+behavior = \"shoot\"
+shots_per_second = 1.0
+ricochets = 2
+".parse().unwrap();
+    let enemy = archetypes.spawn("bouncer", Vector::new(0.0, 0.0)).unwrap();
+    assert_eq!(enemy.weapon().ricochets(), 2);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::archetype::Archetypes;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::replay::{self, Replay};
+use game_objects::world::{World, InputSnapshot};
+
+const ARENA: &str = "\
+##########
+#P.......#
+#........#
+#........#
+##########
+";
+
+fn data() -> u64 {
+    replay::data_hash(&[ARENA])
+}
+
+fn world(seed: u64) -> World {
+    World::new(ARENA.parse::<GameMap>().unwrap(), common::player(), Archetypes::default(), seed)
+}
+
+fn recorded_run(seed: u64) -> (Replay, World) {
+    let mut world = world(seed);
+    let mut replay = Replay::new(seed, "arena", data());
+    for tick in 0..900 {
+        let input = InputSnapshot {
+            move_right: tick % 200 < 100,
+            move_down: tick % 300 > 150,
+            aim_up: tick % 50 < 10,
+            shoot: tick % 3 == 0,
+            next_weapon: tick == 300,
+            sticks: if tick == 400 { Some((Vector::new(0.25, -0.5), Vector::new(0.0, 1.0))) } else { None },
+            ..InputSnapshot::default()
+        };
+        replay.record(&input);
+        world.tick(&input);
+    }
+    (replay, world)
+}
+
+#[test]
+fn replays_survive_a_round_trip() {
+    let (replay, _) = recorded_run(7);
+    let bytes = replay.to_bytes();
+    let loaded = Replay::from_bytes(&bytes).unwrap();
+    assert!(loaded == replay);
+    assert_eq!(loaded.seed(), 7);
+    assert_eq!(loaded.level(), "arena");
+    assert_eq!(loaded.data(), data());
+}
+
+#[test]
+fn levels_longer_than_64_kib_survive_a_round_trip() {
+    let level = "#".repeat(70_000);
+    let replay = Replay::new(3, &level, data());
+    let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+    assert_eq!(loaded.level(), level);
+}
+
+#[test]
+fn held_keys_are_stored_once() {
+    let mut replay = Replay::new(0, "arena", data());
+    let input = InputSnapshot { move_left: true, ..InputSnapshot::default() };
+    for _ in 0..600 {
+        replay.record(&input);
+    }
+    assert!(replay.to_bytes().len() < 42);
+}
+
+#[test]
+fn headless_playback_matches_the_recorded_run() {
+    let (replay, recorded) = recorded_run(99);
+    let mut played = world(replay.seed());
+    replay.play(&mut played);
+
+    assert_eq!(played.player().position(), recorded.player().position());
+    assert_eq!(played.player().health(), recorded.player().health());
+    let positions = |world: &World| world.enemies().iter().map(GameObject::position).collect::<Vec<_>>();
+    assert_eq!(positions(&played), positions(&recorded));
+}
+
+#[test]
+fn truncated_replays_are_rejected() {
+    let (replay, _) = recorded_run(1);
+    let bytes = replay.to_bytes();
+    assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Replay::from_bytes(b"nope").is_err());
+}
+
+#[test]
+fn replays_from_other_versions_are_rejected() {
+    let (replay, _) = recorded_run(1);
+    let mut bytes = replay.to_bytes();
+    bytes[4] = 2;
+    assert!(Replay::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn the_data_hash_tells_files_apart() {
+    assert_eq!(replay::data_hash(&[ARENA, "[chaser]"]), replay::data_hash(&[ARENA, "[chaser]"]));
+    assert_ne!(replay::data_hash(&[ARENA, "[chaser]"]), replay::data_hash(&[ARENA, "[tank]"]));
+    // moving text from one file to the next still counts as a change
+    assert_ne!(replay::data_hash(&["ab", "c"]), replay::data_hash(&["a", "bc"]));
+    // fixed across builds, so saved replays keep matching
+    assert_eq!(replay::data_hash(&[]), 0xcbf2_9ce4_8422_2325);
+}
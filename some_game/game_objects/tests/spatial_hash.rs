@@ -0,0 +1,82 @@
+use quicksilver::geom::{Vector, Rectangle, Circle};
+
+use game_objects::spatial_hash::SpatialHash;
+
+fn square(x: f32, y: f32, size: f32) -> Rectangle {
+    Rectangle::new(Vector::new(x, y), Vector::new(size, size))
+}
+
+fn sorted(mut ids: Vec<usize>) -> Vec<usize> {
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn finds_what_overlaps_a_rectangle() {
+    let mut grid = SpatialHash::new(32.0);
+    grid.insert(7, square(10.0, 10.0, 8.0));
+    grid.insert(3, square(100.0, 10.0, 8.0));
+    grid.insert(5, square(14.0, 14.0, 8.0));
+    assert_eq!(grid.len(), 3);
+
+    // ids are the caller's, not insertion order
+    assert_eq!(sorted(grid.query_rectangle(&square(0.0, 0.0, 16.0))), vec![5, 7]);
+    assert_eq!(grid.query_rectangle(&square(98.0, 8.0, 4.0)), vec![3]);
+    // same cell, but not touching
+    assert!(grid.query_rectangle(&square(24.0, 0.0, 4.0)).is_empty());
+    // nothing there at all
+    assert!(grid.query_rectangle(&square(500.0, 500.0, 10.0)).is_empty());
+}
+
+#[test]
+fn big_entries_are_found_from_every_cell_and_reported_once() {
+    let mut grid = SpatialHash::new(16.0);
+    // covers 5 x 5 cells
+    grid.insert(1, square(4.0, 4.0, 70.0));
+
+    for corner in [Vector::new(5.0, 5.0), Vector::new(70.0, 5.0), Vector::new(5.0, 70.0), Vector::new(70.0, 70.0)] {
+        assert_eq!(grid.query_rectangle(&Rectangle::new(corner, Vector::new(1.0, 1.0))), vec![1]);
+    }
+    // a query spanning all of its cells still lists it only once
+    assert_eq!(grid.query_rectangle(&square(0.0, 0.0, 100.0)), vec![1]);
+    assert_eq!(grid.query_circle(&Circle::new(Vector::new(40.0, 40.0), 60.0)), vec![1]);
+}
+
+#[test]
+fn works_at_negative_coordinates() {
+    let mut grid = SpatialHash::new(32.0);
+    grid.insert(1, square(-40.0, -40.0, 10.0));
+    grid.insert(2, square(-5.0, -5.0, 10.0));
+
+    assert_eq!(grid.query_rectangle(&square(-38.0, -38.0, 2.0)), vec![1]);
+    // straddles the cells on both sides of 0
+    assert_eq!(grid.query_rectangle(&square(3.0, 3.0, 1.0)), vec![2]);
+    assert_eq!(grid.query_rectangle(&square(-3.0, -3.0, 1.0)), vec![2]);
+    assert_eq!(sorted(grid.query_circle(&Circle::new(Vector::new(-20.0, -20.0), 25.0))), vec![1, 2]);
+}
+
+#[test]
+fn circles_only_report_what_they_touch() {
+    let mut grid = SpatialHash::new(32.0);
+    grid.insert(1, square(45.0, 0.0, 4.0));
+    // inside the circle's bounding box, but outside the circle
+    grid.insert(2, square(42.0, 42.0, 4.0));
+
+    assert_eq!(grid.query_circle(&Circle::new(Vector::new(0.0, 0.0), 50.0)), vec![1]);
+}
+
+#[test]
+fn clear_forgets_everything() {
+    let mut grid = SpatialHash::new(32.0);
+    grid.insert(1, square(0.0, 0.0, 10.0));
+    grid.insert(2, square(64.0, 64.0, 10.0));
+    grid.clear();
+
+    assert!(grid.is_empty());
+    assert!(grid.query_rectangle(&square(-100.0, -100.0, 300.0)).is_empty());
+
+    // and can be filled again
+    grid.insert(3, square(64.0, 64.0, 10.0));
+    assert_eq!(grid.len(), 1);
+    assert_eq!(grid.query_rectangle(&square(0.0, 0.0, 100.0)), vec![3]);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use quicksilver::geom::{Vector, Rectangle};
+
+use game_objects::game_map::GameMap;
+use game_objects::steering;
+use game_objects::world::{World, InputSnapshot};
+
+fn zero() -> Vector {
+    Vector::new(0.0, 0.0)
+}
+
+#[test]
+fn seek_heads_for_the_target_at_full_speed() {
+    let force = steering::seek(zero(), zero(), Vector::new(10.0, 0.0), 4.0);
+    assert_eq!(force, Vector::new(4.0, 0.0));
+    // already going there flat out, nothing to change
+    let force = steering::seek(zero(), Vector::new(4.0, 0.0), Vector::new(10.0, 0.0), 4.0);
+    assert_eq!(force, zero());
+}
+
+#[test]
+fn arrive_slows_down_near_the_target() {
+    let far = steering::arrive(zero(), zero(), Vector::new(100.0, 0.0), 4.0, 32.0);
+    let near = steering::arrive(zero(), zero(), Vector::new(8.0, 0.0), 4.0, 32.0);
+    assert_eq!(far, Vector::new(4.0, 0.0));
+    assert_eq!(near, Vector::new(1.0, 0.0));
+}
+
+#[test]
+fn separation_pushes_away_from_close_neighbours() {
+    let neighbours = vec![(0, zero()), (1, Vector::new(5.0, 0.0)), (2, Vector::new(100.0, 0.0))];
+    let push = steering::separation(0, zero(), neighbours.into_iter(), 10.0);
+    assert!(push.x < 0.0);
+    assert_eq!(push.y, 0.0);
+
+    // a perfect stack still comes apart, in opposite directions
+    let first = steering::separation(0, zero(), [(1, zero())].into_iter(), 10.0);
+    let second = steering::separation(1, zero(), [(0, zero())].into_iter(), 10.0);
+    assert!(first.len() > 0.0);
+    assert_eq!(first, -second);
+}
+
+#[test]
+fn walls_ahead_push_back() {
+    let map: GameMap = "\
+#####
+#...#
+#####
+".parse().unwrap();
+    let area = Rectangle::new(Vector::new(40.0, 40.0), Vector::new(12.0, 12.0));
+    assert_eq!(steering::avoid_walls(&map, &area, Vector::new(1.0, 0.0), 8.0), zero());
+    let push = steering::avoid_walls(&map, &area, Vector::new(0.0, -2.0), 8.0);
+    assert!(push.y > 0.0);
+}
+
+#[test]
+fn stacked_enemies_spread_out() {
+    let map: GameMap = "\
+############
+#P.........#
+#..........#
+#..........#
+#..........#
+############
+".parse().unwrap();
+    // short sighted and only spawned by hand, so they just stand around
+    let mut archetypes = common::archetypes();
+    let mut chaser = common::chaser();
+    chaser.range = 1.0;
+    chaser.ai.idle_frames = 10_000;
+    archetypes.insert("chaser", chaser);
+
+    let mut world = World::new(map, common::player(), archetypes, 0);
+    for _ in 0..5 {
+        world.spawn_enemy("chaser", Vector::new(200.0, 90.0));
+    }
+    for _ in 0..600 {
+        world.tick(&InputSnapshot::default());
+    }
+
+    let enemies = world.enemies();
+    for (index, enemy) in enemies.iter().enumerate() {
+        for other in enemies.iter().skip(index + 1) {
+            assert!((enemy.center() - other.center()).len() >= enemy.size().x);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use quicksilver::geom::Vector;
+
+use game_objects::GameObjectType;
+use game_objects::map_layout::{MapLayout, MapError};
+use game_objects::tiled;
+
+fn check_small_fixture(layout: &MapLayout) {
+    assert_eq!(layout.width(), 5);
+    assert_eq!(layout.height(), 3);
+
+    let corner = layout.tile(0, 0).unwrap();
+    assert!(corner.kind == GameObjectType::Wall && corner.collidable);
+
+    let floor = layout.tile(1, 1).unwrap();
+    assert!(floor.kind == GameObjectType::Floor && !floor.collidable);
+
+    // floor tile marked collidable through its properties
+    let blocked = layout.tile(2, 1).unwrap();
+    assert!(blocked.kind == GameObjectType::Floor && blocked.collidable);
+
+    assert_eq!(layout.player_spawn(), Some((1, 1)));
+    assert_eq!(layout.enemy_spawns(), &vec![(3, 1)]);
+
+    // 16px Tiled tiles are scaled onto the 32px game grid
+    let exit = &layout.triggers()[0];
+    assert_eq!(exit.name, "exit");
+    assert_eq!(exit.area.pos, Vector::new(32.0, 32.0));
+    assert_eq!(exit.area.size, Vector::new(64.0, 32.0));
+}
+
+#[test]
+fn imports_tmx_fixture() {
+    let layout = tiled::load("tests/fixtures/small.tmx").unwrap();
+    check_small_fixture(&layout);
+}
+
+#[test]
+fn imports_json_fixture() {
+    let layout = tiled::load("tests/fixtures/small.json").unwrap();
+    check_small_fixture(&layout);
+}
+
+#[test]
+fn rejects_short_tile_layer() {
+    let xml = r#"<map width="2" height="2" tilewidth="32" tileheight="32">
+        <layer><data encoding="csv">1,1,1</data></layer>
+    </map>"#;
+    assert!(matches!(tiled::from_tmx(xml), Err(MapError::Tiled(_))));
+}
+
+// a 4x3 map, walls around two floor cells at (1, 1) and (2, 1), with the given objects
+fn tmx_with_objects(objects: &str) -> String {
+    format!(r#"<map width="4" height="3" tilewidth="16" tileheight="16">
+        <tileset firstgid="1"><tile id="0" class="wall"/><tile id="1" class="floor"/></tileset>
+        <layer><data encoding="csv">1,1,1,1,1,2,2,1,1,1,1,1</data></layer>
+        <objectgroup>{}</objectgroup>
+    </map>"#, objects)
+}
+
+fn rejected(objects: &str) -> bool {
+    matches!(tiled::from_tmx(&tmx_with_objects(objects)), Err(MapError::Tiled(_)))
+}
+
+#[test]
+fn objects_on_open_floor_are_placed() {
+    let layout = tiled::from_tmx(&tmx_with_objects(r#"
+        <object name="player" type="player_spawn" x="16" y="16"/>
+        <object name="enemy" type="enemy_spawn" x="47" y="31"/>
+        <object name="gem 2" type="pickup" x="40" y="20"/>
+    "#)).unwrap();
+    assert_eq!(layout.player_spawn(), Some((1, 1)));
+    assert_eq!(layout.enemy_spawns(), &vec![(2, 1)]);
+    assert_eq!(layout.pickups().len(), 1);
+}
+
+#[test]
+fn rejects_objects_outside_the_map() {
+    assert!(rejected(r#"<object name="player" type="player_spawn" x="64" y="16"/>"#));
+    assert!(rejected(r#"<object name="enemy" type="enemy_spawn" x="16" y="48"/>"#));
+    // left of the map, which used to be clamped onto column 0
+    assert!(rejected(r#"<object name="enemy" type="enemy_spawn" x="-8" y="16"/>"#));
+    assert!(rejected(r#"<object name="gem" type="pickup" x="200" y="200"/>"#));
+}
+
+#[test]
+fn rejects_objects_on_collidable_tiles() {
+    assert!(rejected(r#"<object name="player" type="player_spawn" x="0" y="0"/>"#));
+    assert!(rejected(r#"<object name="enemy" type="enemy_spawn" x="48" y="16"/>"#));
+    assert!(rejected(r#"<object name="gem" type="pickup" x="16" y="32"/>"#));
+}
+
+#[test]
+fn rejects_a_second_player_spawn() {
+    assert!(rejected(r#"
+        <object name="player" type="player_spawn" x="16" y="16"/>
+        <object name="player" type="player_spawn" x="32" y="16"/>
+    "#));
+}
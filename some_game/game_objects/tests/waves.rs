@@ -0,0 +1,197 @@
+mod common;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use quicksilver::geom::Vector;
+
+use game_objects::events::GameEvent;
+use game_objects::game_map::GameMap;
+use game_objects::map_layout::MapLayout;
+use game_objects::waves::{Waves, WaveDirector, WaveError};
+use game_objects::world::{World, InputSnapshot};
+
+const GAME_WAVES: &str = include_str!("../../game/waves.toml");
+const GAME_ENEMIES: &str = include_str!("../../game/enemies.toml");
+
+// one second of break, then two chasers half a second apart; every wave
+// brings half as many again as the first
+const TWO_CHASERS: &str = "
+spawn_distance = 128.0
+break_seconds = 1.0
+
+[escalation]
+count = 0.5
+health = 1.0
+speed = 0.1
+
+[[wave]]
+interval = 0.5
+enemies = { chaser = 2 }
+";
+
+// runs the director until a wave has spawned everything, with the given number
+// of enemies alive, and returns what it spawned and the events on the way
+fn run_wave(director: &mut WaveDirector, rng: &mut StdRng, alive: usize) -> (Vec<String>, Vec<GameEvent>) {
+    let archetypes = common::archetypes();
+    let mut spawned = Vec::new();
+    let mut events = Vec::new();
+    for _ in 0..600 {
+        spawned.extend(director.tick(&archetypes, alive, rng, &mut events));
+        if !director.is_on_break() && director.enemies_to_spawn() == 0 {
+            break;
+        }
+    }
+    (spawned, events)
+}
+
+fn world(map: &str) -> World {
+    let mut world = World::new(map.parse::<GameMap>().unwrap(), common::player(), common::archetypes(), 0);
+    world.set_waves(TWO_CHASERS.parse().unwrap());
+    world
+}
+
+#[test]
+fn the_games_wave_file_loads() {
+    let waves: Waves = GAME_WAVES.parse().unwrap();
+    assert!(!waves.waves.is_empty());
+    assert!(waves.check(&GAME_ENEMIES.parse().unwrap()).is_ok());
+}
+
+#[test]
+fn bad_definitions_are_rejected() {
+    let typo = TWO_CHASERS.replace("interval", "intervall");
+    assert!(matches!(typo.parse::<Waves>(), Err(WaveError::Parse(_))));
+    assert!(matches!("".parse::<Waves>(), Err(WaveError::Parse(_))));
+    assert!(matches!("wave = []".parse::<Waves>(), Err(WaveError::Empty)));
+
+    let dragons: Waves = TWO_CHASERS.replace("chaser", "dragon").parse().unwrap();
+    assert!(matches!(dragons.check(&common::archetypes()), Err(WaveError::UnknownEnemy(name)) if name == "dragon"));
+}
+
+#[test]
+fn waves_follow_each_other_once_cleared() {
+    let mut director = WaveDirector::new(TWO_CHASERS.parse().unwrap());
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let (spawned, events) = run_wave(&mut director, &mut rng, 0);
+    assert_eq!(spawned, vec!["chaser", "chaser"]);
+    assert_eq!(events, vec![GameEvent::WaveStarted { wave: 1 }]);
+
+    // nothing happens while the wave's enemies are alive
+    let mut events = Vec::new();
+    for _ in 0..600 {
+        assert!(director.tick(&common::archetypes(), 2, &mut rng, &mut events).is_none());
+    }
+    assert!(events.is_empty());
+
+    director.tick(&common::archetypes(), 0, &mut rng, &mut events);
+    assert_eq!(events, vec![GameEvent::WaveCleared { wave: 1 }]);
+    assert!(director.is_on_break());
+
+    // the second wave is half as big again
+    let (spawned, events) = run_wave(&mut director, &mut rng, 0);
+    assert_eq!(spawned.len(), 3);
+    assert_eq!(events, vec![GameEvent::WaveStarted { wave: 2 }]);
+}
+
+#[test]
+fn later_waves_spawn_tougher_enemies() {
+    let mut director = WaveDirector::new(TWO_CHASERS.parse().unwrap());
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..3 {
+        run_wave(&mut director, &mut rng, 0);
+        let mut events = Vec::new();
+        director.tick(&common::archetypes(), 0, &mut rng, &mut events);
+    }
+    run_wave(&mut director, &mut rng, 0);
+    assert_eq!(director.wave(), 4);
+
+    let chaser = common::archetypes().spawn("chaser", Vector::new(0.0, 0.0)).unwrap();
+    let mut escalated = chaser.clone();
+    director.escalate(&mut escalated);
+    assert_eq!(escalated.max_health(), chaser.max_health() + 3);
+    assert!((escalated.max_speed() - chaser.max_speed() * 1.3).abs() < 0.001);
+}
+
+#[test]
+fn enemies_spawn_on_spawn_tiles_away_from_the_player() {
+    // the spawn next to the player is too close, only the far one is used
+    let mut world = world("\
+##########
+#PE......#
+#......E.#
+##########
+");
+    for _ in 0..180 {
+        let known = world.enemies().len();
+        world.tick(&InputSnapshot::default());
+        for enemy in world.enemies().iter().skip(known) {
+            assert_eq!(world.map().layout().cell_at(enemy.center()), Some((7, 2)));
+        }
+    }
+    assert_eq!(world.enemies().len(), 2);
+}
+
+#[test]
+fn enemies_spawn_on_distant_floor_without_spawn_tiles() {
+    let mut open = world("\
+##########
+#P.#.....#
+#..#.....#
+##########
+");
+    for _ in 0..180 {
+        let known = open.enemies().len();
+        open.tick(&InputSnapshot::default());
+        for enemy in open.enemies().iter().skip(known) {
+            let (x, y) = open.map().layout().cell_at(enemy.center()).unwrap();
+            assert!(open.map().layout().is_walkable(x, y));
+            assert!((MapLayout::cell_center((x, y)) - open.player().center()).len() >= 128.0);
+        }
+    }
+    assert_eq!(open.enemies().len(), 2);
+
+    // with nowhere far enough away the wave waits
+    let mut cramped = world("\
+#####
+#P..#
+#####
+");
+    for _ in 0..180 {
+        cramped.tick(&InputSnapshot::default());
+    }
+    assert!(cramped.enemies().is_empty());
+    assert_eq!(cramped.director().enemies_to_spawn(), 2);
+}
+
+#[test]
+fn enemies_placed_by_the_map_do_not_hold_up_a_wave() {
+    // a harmless guard stands behind the player, the wave's chaser comes from the right
+    let mut guard = common::chaser();
+    guard.spawn_weight = 1;
+    guard.range = 1.0;
+    guard.damage = 0;
+    let mut archetypes = common::archetypes();
+    archetypes.insert("guard", guard);
+    let map = "\
+################
+#EP............#
+################
+";
+    let mut world = World::new(map.parse::<GameMap>().unwrap(), common::player(), archetypes, 0);
+    world.set_waves("[[wave]]\nenemies = { chaser = 1 }".parse().unwrap());
+    assert_eq!(world.enemies().len(), 1);
+    assert_eq!(world.enemies()[0].wave(), None);
+
+    let shoot = InputSnapshot { shoot: true, ..InputSnapshot::default() };
+    let mut events = Vec::new();
+    for _ in 0..1200 {
+        events.extend(world.tick(&shoot));
+        if events.contains(&GameEvent::WaveCleared { wave: 1 }) {
+            break;
+        }
+    }
+    assert!(events.contains(&GameEvent::WaveCleared { wave: 1 }));
+    assert_eq!(world.enemies().len(), 1);
+    assert_eq!(world.enemies()[0].wave(), None);
+}
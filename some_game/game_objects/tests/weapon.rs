@@ -0,0 +1,141 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::archetype::Archetypes;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::weapon::{Weapon, Weapons, WeaponError};
+use game_objects::world::{World, InputSnapshot};
+
+const GAME_WEAPONS: &str = include_str!("../../game/weapons.toml");
+
+const CORRIDOR: &str = "\
+####################
+#P.................#
+####################
+";
+
+// chasers too short sighted to come at the player
+fn archetypes() -> Archetypes {
+    let mut chaser = common::chaser();
+    chaser.range = 1.0;
+    let mut archetypes = common::archetypes();
+    archetypes.insert("chaser", chaser);
+    archetypes
+}
+
+fn world() -> World {
+    let mut world = World::new(CORRIDOR.parse::<GameMap>().unwrap(), common::player(), archetypes(), 0);
+    world.set_weapons(GAME_WEAPONS.parse().unwrap());
+    world
+}
+
+// ticks until the first shot is out
+fn fire(world: &mut World) {
+    let shoot = InputSnapshot { shoot: true, ..InputSnapshot::default() };
+    while world.bullets().is_empty() {
+        world.tick(&shoot);
+    }
+}
+
+#[test]
+fn the_games_weapon_file_loads_in_slot_order() {
+    let weapons: Weapons = GAME_WEAPONS.parse().unwrap();
+    assert_eq!(weapons.names(), vec!["pistol", "shotgun", "machine_gun", "rail", "grenade"]);
+}
+
+#[test]
+fn bad_definitions_are_rejected() {
+    assert!(matches!("[pistol]\nsped = 3.0".parse::<Weapons>(), Err(WeaponError::Parse(_))));
+    assert!(matches!("".parse::<Weapons>(), Err(WeaponError::Empty)));
+    for rate in ["0.0", "-2.0", "nan", "inf"] {
+        let text = format!("[pistol]\nshots_per_second = {}", rate);
+        assert!(matches!(text.parse::<Weapons>(), Err(WeaponError::Parse(_))), "{}", rate);
+    }
+}
+
+#[test]
+fn shotguns_fan_their_pellets_around_the_aim() {
+    let mut shooter = common::player();
+    shooter.equip(&Weapon { projectiles: 5, spread: 90.0, speed: 10.0, ..Weapon::default() });
+    for _ in 0..60 {
+        shooter.update_timers();
+    }
+    let mut bullets = Vec::new();
+    shooter.shoot(&mut bullets);
+
+    assert_eq!(bullets.len(), 5);
+    let velocities: Vec<Vector> = bullets.iter().map(GameObject::velocity).collect();
+    assert!((velocities[2] - Vector::new(10.0, 0.0)).len() < 0.001);
+    assert!((velocities[0].y + velocities[4].y).abs() < 0.001);
+    assert!((velocities[0].y.atan2(velocities[0].x).to_degrees() + 45.0).abs() < 0.01);
+    assert!(bullets.iter().all(|bullet| (bullet.velocity().len() - 10.0).abs() < 0.001));
+}
+
+#[test]
+fn switching_cycles_through_the_weapons() {
+    let mut world = world();
+    assert_eq!(world.weapon(), "pistol");
+    let next = InputSnapshot { next_weapon: true, ..InputSnapshot::default() };
+    let previous = InputSnapshot { previous_weapon: true, ..InputSnapshot::default() };
+
+    world.tick(&next);
+    world.tick(&next);
+    assert_eq!(world.weapon(), "machine_gun");
+    assert_eq!(world.player().loadout(), world.weapons().get("machine_gun").unwrap());
+
+    world.tick(&previous);
+    world.tick(&previous);
+    world.tick(&previous);
+    assert_eq!(world.weapon(), "grenade");
+}
+
+#[test]
+fn machine_guns_outshoot_pistols() {
+    let shots = |switches: usize| {
+        let mut world = world();
+        for _ in 0..switches {
+            world.tick(&InputSnapshot { next_weapon: true, ..InputSnapshot::default() });
+        }
+        for _ in 0..60 {
+            world.tick(&InputSnapshot { shoot: true, ..InputSnapshot::default() });
+        }
+        world.bullets().len()
+    };
+    assert!(shots(2) > shots(0) * 4);
+}
+
+#[test]
+fn rails_go_through_a_line_of_enemies() {
+    let mut world = world();
+    for _ in 0..3 {
+        world.tick(&InputSnapshot { next_weapon: true, ..InputSnapshot::default() });
+    }
+    assert_eq!(world.weapon(), "rail");
+    for x in [200.0, 260.0, 320.0] {
+        world.spawn_enemy("chaser", Vector::new(x, 42.0));
+    }
+
+    fire(&mut world);
+    for _ in 0..40 {
+        world.tick(&InputSnapshot::default());
+    }
+    // three damage kills each of them
+    assert!(world.enemies().is_empty());
+}
+
+#[test]
+fn pistol_bullets_stop_at_the_first_enemy() {
+    let mut world = world();
+    for x in [200.0, 260.0] {
+        world.spawn_enemy("chaser", Vector::new(x, 42.0));
+    }
+
+    fire(&mut world);
+    for _ in 0..60 {
+        world.tick(&InputSnapshot::default());
+    }
+    assert_eq!(world.enemies()[0].health(), world.enemies()[0].max_health() - 1);
+    assert_eq!(world.enemies()[1].health(), world.enemies()[1].max_health());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use quicksilver::geom::Vector;
+
+use game_objects::archetype::Archetypes;
+use game_objects::game_map::GameMap;
+use game_objects::game_object::GameObject;
+use game_objects::sprite::SpriteId;
+use game_objects::world::{World, InputSnapshot};
+
+const ARENA: &str = "\
+##########
+#P.......#
+#........#
+##########
+";
+
+fn world() -> World {
+    seeded_world(0)
+}
+
+fn seeded_world(seed: u64) -> World {
+    World::new(ARENA.parse::<GameMap>().unwrap(), common::player(), Archetypes::default(), seed)
+}
+
+#[test]
+fn player_starts_on_spawn() {
+    let world = world();
+    assert_eq!(world.player().position(), Vector::new(32.0, 32.0));
+}
+
+#[test]
+fn walls_stop_the_player() {
+    let mut world = world();
+    let input = InputSnapshot { move_left: true, ..InputSnapshot::default() };
+    for _ in 0..120 {
+        world.tick(&input);
+    }
+    assert!(world.player().position().x >= 32.0);
+}
+
+#[test]
+fn shooting_spawns_a_bullet_once_the_cooldown_is_up() {
+    let mut world = world();
+    let input = InputSnapshot { shoot: true, ..InputSnapshot::default() };
+    world.tick(&input);
+    assert!(world.bullets().is_empty());
+    for _ in 0..60 {
+        world.tick(&input);
+    }
+    assert!(!world.bullets().is_empty());
+}
+
+#[test]
+fn game_objects_serialize_without_graphics() {
+    let json = serde_json::to_string(world().player()).unwrap();
+    let player: GameObject = serde_json::from_str(&json).unwrap();
+    assert_eq!(player.image(), SpriteId::ArrowRight);
+}
+
+#[test]
+fn rendering_interpolates_between_ticks() {
+    let mut world = world();
+    let input = InputSnapshot { move_right: true, ..InputSnapshot::default() };
+    for _ in 0..10 {
+        world.tick(&input);
+    }
+    let player = world.player();
+    assert!(player.position().x > player.previous_position().x);
+    assert_eq!(player.interpolated_sprite(0.0).pos, player.previous_position());
+    assert_eq!(player.interpolated_sprite(1.0).pos, player.position());
+    let halfway = player.interpolated_sprite(0.5).pos.x;
+    assert!(halfway > player.previous_position().x && halfway < player.position().x);
+}
+
+#[test]
+fn same_seed_and_inputs_replay_identically() {
+    let inputs = [
+        InputSnapshot { move_right: true, shoot: true, ..InputSnapshot::default() },
+        InputSnapshot { move_down: true, aim_down: true, ..InputSnapshot::default() },
+        InputSnapshot::default(),
+    ];
+    let mut first = seeded_world(42);
+    let mut second = seeded_world(42);
+    for tick in 0..600 {
+        let input = inputs[tick / 50 % inputs.len()];
+        first.tick(&input);
+        second.tick(&input);
+    }
+    let positions = |world: &World| world.enemies().iter().map(GameObject::position).collect::<Vec<_>>();
+    assert!(!first.enemies().is_empty());
+    assert_eq!(positions(&first), positions(&second));
+    assert_eq!(first.player().position(), second.player().position());
+}